@@ -0,0 +1,280 @@
+//! The boss fight in progress, published as timer variables, and splits on a boss's health.
+
+use std::time::Duration;
+
+use asr::settings::Map;
+
+use crate::{
+    dashboard::Dashboard,
+    game_data::{BossData, BossKind},
+    lss::setting_entries,
+    settings::{BossHealthSplits, Settings},
+};
+
+/// The `.lss` setting listing the health splits, a string like `Asahi:50, Sakura Shogun:66` or a list with an entry per split.
+pub const BOSS_HEALTH_SETTING: &str = "boss_health_splits";
+
+/// Completed health splits are kept under this prefix, they are not settings.
+pub const BOSS_HEALTH_PREFIX: &str = "boss_health__";
+
+/// The game marks a running boss fight with either of the two flags.
+pub fn fight_in_progress(boss: &BossData) -> bool {
+    boss.in_progress || boss.override_in_progress
+}
+
+/// Which fight against the boss this is, the two Karasu Tengu fights share a `BossKind` and the duo (225 health) is the second one.
+pub fn fight_index(boss: &BossData) -> u32 {
+    match boss.boss_kind {
+        BossKind::KarasuTengu if boss.total_health == 225.0 => 2,
+        _ => 1,
+    }
+}
+
+/// The name of the fight against `boss`.
+pub fn fight_name(boss: &BossData) -> &'static str {
+    match (boss.boss_kind, fight_index(boss)) {
+        (BossKind::KarasuTengu, 2) => "Karasu Tengu Duo",
+        (kind, _) => kind.name(),
+    }
+}
+
+/// A split for when a boss's health drops to `percent`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HealthSplit {
+    pub boss: BossKind,
+    pub percent: f32,
+}
+
+impl HealthSplit {
+    /// Parses a `Boss:percent` entry.
+    pub fn parse(entry: &str) -> Result<Self, String> {
+        let (boss, percent) = entry
+            .rsplit_once(':')
+            .ok_or_else(|| format!("expected `Boss:percent` found '{}'", entry))?;
+        let boss =
+            BossKind::from_name(boss).ok_or_else(|| format!("unknown boss '{}'", boss.trim()))?;
+        let percent = percent
+            .trim()
+            .parse::<f32>()
+            .map_err(|e| format!("'{}': {}", entry, e))?;
+        if !(percent > 0.0 && percent < 100.0) {
+            return Err(format!(
+                "'{}': the percent must be between 0 and 100",
+                entry
+            ));
+        }
+        Ok(HealthSplit { boss, percent })
+    }
+
+    /// The splits of the splits file with the `percents` of the settings added for every boss.
+    pub fn with_percents(splits: &[HealthSplit], percents: &[f32]) -> Vec<HealthSplit> {
+        let mut all = splits.to_vec();
+        for boss in BossKind::ALL.into_iter().filter(|b| b.has_fight()) {
+            for percent in percents {
+                let split = HealthSplit {
+                    boss,
+                    percent: *percent,
+                };
+                if !all.contains(&split) {
+                    all.push(split);
+                }
+            }
+        }
+        all
+    }
+
+    /// The key of this split during the `fight`th fight against its boss in the completed splits.
+    pub fn key(&self, fight: u32) -> String {
+        format!(
+            "{}{:?}_{}_{}",
+            BOSS_HEALTH_PREFIX, self.boss, fight, self.percent
+        )
+    }
+
+    /// Returns true when the health of `boss` went from above this threshold to at or below it.
+    pub fn crossed(&self, boss: BossKind, old_percent: f32, new_percent: f32) -> bool {
+        self.boss == boss && old_percent > self.percent && new_percent <= self.percent
+    }
+}
+
+/// The health splits of the splits file and of the boss health setting.
+#[derive(Debug, Default)]
+pub struct HealthSplits {
+    /// The `boss_health_splits` of the `.lss`.
+    file: Vec<HealthSplit>,
+    /// The boss health setting `all` was built for.
+    percents: Option<BossHealthSplits>,
+    /// Every health split, rebuilt when the file or the setting changes.
+    pub all: Vec<HealthSplit>,
+}
+
+impl HealthSplits {
+    /// Reads the health splits of the `.lss` settings, returning an error for every entry that can't be read (those are left out).
+    pub fn load(&mut self, map: &Map) -> Vec<String> {
+        let mut errors = vec![];
+        self.file.clear();
+        for entry in setting_entries(map, BOSS_HEALTH_SETTING, ',') {
+            match HealthSplit::parse(&entry) {
+                Ok(split) => self.file.push(split),
+                Err(err) => errors.push(err),
+            }
+        }
+        self.percents = None;
+        errors
+    }
+
+    /// Adds the splits of the boss health setting when it changed.
+    pub fn update(&mut self, settings: &Settings) {
+        if self.percents != Some(settings.boss_health_percents) {
+            self.percents = Some(settings.boss_health_percents);
+            self.all =
+                HealthSplit::with_percents(&self.file, settings.boss_health_percents.percents());
+        }
+    }
+
+    /// The number of times these split in a run, Karasu Tengu splits in both its fights.
+    pub fn count(&self) -> usize {
+        self.all.iter().map(|split| split.boss.fights()).sum()
+    }
+}
+
+/// The boss fight that is running, or the last one if none is.
+#[derive(Debug, Default)]
+pub struct BossFight {
+    pub boss: Option<BossKind>,
+    /// Which fight against `boss`, see [`fight_index`].
+    pub fight: u32,
+    /// The game time the fight started at, in seconds.
+    started: Option<f32>,
+    /// The length of the fight once it is over.
+    ended: Option<Duration>,
+}
+
+impl BossFight {
+    pub fn start(&mut self, boss: &BossData, time_played: f32) {
+        self.boss = Some(boss.boss_kind);
+        self.fight = fight_index(boss);
+        self.started = Some(time_played);
+        self.ended = None;
+    }
+
+    /// Stops the fight time, on a defeat or when the fight is left.
+    pub fn end(&mut self, time_played: f32) {
+        if self.ended.is_none() {
+            self.ended = self.elapsed(time_played);
+        }
+    }
+
+    /// The boss of the fight that is running.
+    pub fn running(&self) -> Option<BossKind> {
+        self.boss.filter(|_| self.ended.is_none())
+    }
+
+    /// Returns true while a fight against `boss` is running.
+    pub fn is_running(&self, boss: BossKind) -> bool {
+        self.running() == Some(boss)
+    }
+
+    /// The game time spent in the fight at `time_played`, loads and pauses don't count.
+    pub fn elapsed(&self, time_played: f32) -> Option<Duration> {
+        self.ended.or_else(|| {
+            self.started
+                .map(|started| Duration::from_secs_f32((time_played - started).max(0.0)))
+        })
+    }
+
+    /// Sets the "Boss", "Boss health" and "Boss fight time" timer variables.
+    pub fn publish(&self, dashboard: &mut Dashboard, boss_percentage: f32, time_played: f32) {
+        let Some(boss) = self.boss else {
+            return;
+        };
+        dashboard.set("Boss", boss.name());
+        if self.ended.is_none() {
+            dashboard.set("Boss health", &format!("{:.0}%", boss_percentage));
+        }
+        if let Some(elapsed) = self.elapsed(time_played) {
+            dashboard.set("Boss fight time", &format_duration(elapsed));
+        }
+    }
+}
+
+/// Formats `d` like LiveSplit does, `1:23.4`.
+pub fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    let tenths = d.subsec_millis() / 100;
+    match (secs / 3600, secs / 60 % 60, secs % 60) {
+        (0, m, s) => format!("{}:{:02}.{}", m, s, tenths),
+        (h, m, s) => format!("{}:{:02}:{:02}.{}", h, m, s, tenths),
+    }
+}
+
+/// The fights against one boss during a run, times are the game's time played in seconds.
+#[derive(Debug, Clone)]
+pub struct BossStats {
+    pub name: &'static str,
+    /// When the first attempt started.
+    pub started: f32,
+    pub defeated: Option<f32>,
+    pub attempts: u32,
+}
+
+/// The boss fights of the current run.
+#[derive(Debug, Default)]
+pub struct RunStats {
+    bosses: Vec<BossStats>,
+}
+
+impl RunStats {
+    pub fn is_empty(&self) -> bool {
+        self.bosses.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.bosses.clear();
+    }
+
+    /// Counts an attempt at `boss`, the first one is the start of the fight.
+    pub fn fight_started(&mut self, boss: &BossData, time_played: f32) {
+        let name = fight_name(boss);
+        match self.bosses.iter_mut().find(|b| b.name == name) {
+            Some(stats) => stats.attempts += 1,
+            None => self.bosses.push(BossStats {
+                name,
+                started: time_played,
+                defeated: None,
+                attempts: 1,
+            }),
+        }
+    }
+
+    pub fn defeated(&mut self, boss: &BossData, time_played: f32) {
+        let name = fight_name(boss);
+        if let Some(stats) = self.bosses.iter_mut().find(|b| b.name == name) {
+            stats.defeated.get_or_insert(time_played);
+        }
+    }
+
+    /// A table of the fights in the order they were started.
+    pub fn summary(&self) -> String {
+        let time = |secs: f32| format_duration(Duration::from_secs_f32(secs.max(0.0)));
+        let mut out = format!(
+            "{:<36} {:>10} {:>10} {:>10} {:>8}\n",
+            "Boss", "Started", "Defeated", "Fight", "Attempts"
+        );
+        for stats in &self.bosses {
+            let (defeated, fight) = match stats.defeated {
+                Some(defeated) => (time(defeated), time(defeated - stats.started)),
+                None => ("-".to_string(), "-".to_string()),
+            };
+            out.push_str(&format!(
+                "{:<36} {:>10} {:>10} {:>10} {:>8}\n",
+                stats.name,
+                time(stats.started),
+                defeated,
+                fight,
+                stats.attempts
+            ));
+        }
+        out
+    }
+}
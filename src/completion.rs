@@ -0,0 +1,412 @@
+//! How much of the game a save has completed, for 100% runs.
+
+use crate::{boss::fight_name, condition::GameState, game_data::DarumaType};
+
+pub const TOTAL_KODAMAS: i32 = 35;
+pub const TOTAL_VS_TABLETS: i32 = 5;
+pub const TOTAL_FEATHER_KEYS: i32 = 2;
+pub const TOTAL_MUSIC_SHEETS: i32 = 3;
+pub const TOTAL_OMAMORI_STRAPS: i32 = 4;
+
+/// The number of parts `Completion::new` looks at when everything can be read.
+const PARTS: usize = 8;
+
+/// One kind of thing a 100% run has to get.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Part {
+    pub name: &'static str,
+    pub have: usize,
+    pub total: usize,
+    /// The names of what is still missing, empty for counted items like Kodamas.
+    pub missing: Vec<&'static str>,
+}
+
+impl Part {
+    fn counted(name: &'static str, have: i32, total: i32) -> Self {
+        Part {
+            name,
+            have: have.clamp(0, total) as usize,
+            total: total as usize,
+            missing: vec![],
+        }
+    }
+
+    fn named(name: &'static str, items: &[(&'static str, bool)]) -> Self {
+        Part {
+            name,
+            have: items.iter().filter(|(_, got)| *got).count(),
+            total: items.len(),
+            missing: items
+                .iter()
+                .filter(|(_, got)| !got)
+                .map(|(name, _)| *name)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Completion {
+    pub parts: Vec<Part>,
+}
+
+impl Completion {
+    /// The completion of what can be read of the game, parts that can't be read are left out.
+    pub fn new(state: &GameState) -> Self {
+        let mut parts = vec![];
+
+        if let Some(inventory) = state.inventory {
+            parts.push(Part::counted(
+                "Kodamas",
+                inventory.number_of_kodamas,
+                TOTAL_KODAMAS,
+            ));
+            parts.push(Part::counted(
+                "VS tablets",
+                inventory.tablets,
+                TOTAL_VS_TABLETS,
+            ));
+            parts.push(Part::counted(
+                "Music sheets",
+                inventory.music_sheets,
+                TOTAL_MUSIC_SHEETS,
+            ));
+            parts.push(Part::counted(
+                "Omamori straps",
+                inventory.omamori_straps,
+                TOTAL_OMAMORI_STRAPS,
+            ));
+        }
+
+        if let Some(darumas) = state.darumas {
+            let darumas = darumas
+                .iter()
+                // Two of the types are never given to the player
+                .filter(|d| {
+                    !matches!(
+                        d.daruma_type,
+                        DarumaType::SpinAttack | DarumaType::Deprecated1
+                    )
+                })
+                .map(|d| (d.daruma_type.name(), d.available))
+                .collect::<Vec<_>>();
+            parts.push(Part::named("Darumas", &darumas));
+        }
+
+        if let Some(ability) = state.ability {
+            parts.push(Part::named(
+                "Abilities",
+                &[
+                    ("Attack", ability.can_attack),
+                    ("Bat", ability.can_bat),
+                    ("Dash", ability.can_dash),
+                    ("Hover", ability.can_hover),
+                    ("Shade Cloak", ability.can_idash),
+                    ("Grapple", ability.can_grapple),
+                    ("Hammer Dash", ability.can_hammer_dash),
+                    ("Wall Jump", ability.can_wall_jump),
+                ],
+            ));
+        }
+
+        if let Some(quest) = state.quest {
+            // `kitsune_kifuda_end` reads the same flag as `fox_wedding_end`
+            parts.push(Part::named(
+                "Quests",
+                &[
+                    ("Asahi's Staff", quest.asahi_staff_end),
+                    ("Eye of the Beast", quest.asahi_eye_of_beast_end),
+                    ("Bump Prophecy", quest.tori_bump_end),
+                    ("Bat Prophecy", quest.tori_bat_end),
+                    ("Dash Prophecy", quest.tori_dash_end),
+                    ("Shimeji", quest.shimeji_quest_end),
+                    ("Rozu's Requiem", quest.rozus_requiem_end),
+                    ("Fox Wedding", quest.fox_wedding_end),
+                    ("Vermilion Stranger", quest.vermilion_stranger_quest_end),
+                    ("Infinite Kettle", quest.infinite_kettle_end),
+                ],
+            ));
+        }
+
+        if let Some(bosses) = state.bosses {
+            let bosses = bosses
+                .iter()
+                .filter(|b| b.boss_kind.has_fight())
+                .map(|b| (fight_name(b), b.defeated))
+                .collect::<Vec<_>>();
+            parts.push(Part::named("Bosses", &bosses));
+        }
+
+        Completion { parts }
+    }
+
+    /// Returns true when every part could be read and is complete.
+    pub fn is_complete(&self) -> bool {
+        self.parts.len() == PARTS && self.parts.iter().all(|p| p.have == p.total)
+    }
+
+    /// The share of everything that has been got, in percent.
+    pub fn percent(&self) -> f32 {
+        let have = self.parts.iter().map(|p| p.have).sum::<usize>();
+        let total = self.parts.iter().map(|p| p.total).sum::<usize>();
+        if total == 0 {
+            0.0
+        } else {
+            have as f32 * 100.0 / total as f32
+        }
+    }
+
+    /// What is still missing, named items by name and counted items as "3 Kodamas".
+    pub fn missing(&self) -> Vec<String> {
+        self.parts
+            .iter()
+            .filter(|p| p.have < p.total)
+            .flat_map(|p| {
+                if p.missing.is_empty() {
+                    vec![format!("{} {}", p.total - p.have, p.name)]
+                } else {
+                    p.missing.iter().map(|name| name.to_string()).collect()
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_data::{
+        AbilityManager, BossData, BossKind, Daruma, InventoryContainer, QuestManager,
+    };
+
+    const DARUMAS: [DarumaType; 10] = [
+        DarumaType::Bite,
+        DarumaType::Parry,
+        DarumaType::Thorns,
+        DarumaType::Spirits,
+        DarumaType::Bomb,
+        DarumaType::SpinAttack,
+        DarumaType::Deprecated1,
+        DarumaType::FireWall,
+        DarumaType::Ice,
+        DarumaType::Boomerang,
+    ];
+
+    fn inventory(kodamas: i32) -> InventoryContainer {
+        InventoryContainer {
+            feather_keys: TOTAL_FEATHER_KEYS,
+            music_sheets: TOTAL_MUSIC_SHEETS,
+            omamori_straps: TOTAL_OMAMORI_STRAPS,
+            fragile_egg: true,
+            reset_new_game: false,
+            has_kitsune_kifuda: true,
+            base_damage: 1.0,
+            tablets: TOTAL_VS_TABLETS,
+            number_of_kodamas: kodamas,
+        }
+    }
+
+    fn abilities(can_bat: bool) -> AbilityManager {
+        AbilityManager {
+            can_attack: true,
+            can_bat,
+            can_dash: true,
+            can_hover: true,
+            can_idash: true,
+            can_grapple: true,
+            can_hammer_dash: true,
+            can_wall_jump: true,
+        }
+    }
+
+    fn quests() -> QuestManager {
+        QuestManager {
+            asahi_staff_start: true,
+            asahi_staff_end: true,
+            asahi_eye_of_beast_start: true,
+            asahi_eye_of_beast_end: true,
+            asahi_post_armapillo_boss: true,
+            tori_bump_told: true,
+            tori_bump_end: true,
+            tori_bat_told: true,
+            tori_bat_end: true,
+            tori_dash_told: true,
+            tori_dash_end: true,
+            shimeji_armapillos_collect: 4,
+            shimeji_quest_start: true,
+            shimeji_quest_end: true,
+            rozus_requiem_start: true,
+            rozus_requiem_end: true,
+            fox_wedding_start: true,
+            fox_wedding_save_groom: true,
+            fox_wedding_end: true,
+            vermilion_stranger_quest_start: true,
+            vermilion_stranger_quest_end: true,
+            kitsune_kifuda_start: true,
+            kitsune_kifuda_end: true,
+            infinite_kettle_start: true,
+            infinite_kettle_end: true,
+            west_feather_in_keyhole: true,
+            east_feather_in_keyhole: true,
+            defeat_pua_boss: true,
+            defeat_hashihime_boss: true,
+            defeat_kaboto_boss: true,
+            defeat_spider_boss: true,
+            defeat_tengu_boss: true,
+            defeat_gash_boss: true,
+            defeat_asahi_boss: true,
+            defeat_sakura_boss: true,
+            credits_roll: true,
+        }
+    }
+
+    fn boss(boss_kind: BossKind, total_health: f32, defeated: bool) -> BossData {
+        BossData {
+            boss_kind,
+            defeated,
+            in_progress: false,
+            total_health,
+            override_in_progress: false,
+        }
+    }
+
+    /// Every boss of a run, Yuki and Amaterasu have no fight to count.
+    fn bosses(duo_defeated: bool) -> Vec<BossData> {
+        vec![
+            boss(BossKind::KiriKiriBozu, 100.0, true),
+            boss(BossKind::PUA, 100.0, true),
+            boss(BossKind::Hashihime, 100.0, true),
+            boss(BossKind::Yuki, 100.0, false),
+            boss(BossKind::Yokozuna, 100.0, true),
+            boss(BossKind::Jorogumo, 100.0, true),
+            boss(BossKind::KarasuTengu, 133.0, true),
+            boss(BossKind::KarasuTengu, 225.0, duo_defeated),
+            boss(BossKind::DaiTengu, 100.0, true),
+            boss(BossKind::Gasha, 100.0, true),
+            boss(BossKind::Asahi, 100.0, true),
+            boss(BossKind::Shogun, 100.0, true),
+            boss(BossKind::Amaterasu, 100.0, false),
+        ]
+    }
+
+    /// Every daruma available except `missing`, the two types that are never given out are never available.
+    fn darumas(missing: Option<DarumaType>) -> Vec<Daruma> {
+        DARUMAS
+            .into_iter()
+            .map(|daruma_type| Daruma {
+                daruma_type,
+                available: !matches!(
+                    daruma_type,
+                    DarumaType::SpinAttack | DarumaType::Deprecated1
+                ) && Some(daruma_type) != missing,
+                is_active: false,
+                two_eyes: false,
+                stage_one_tea_cost: 1,
+                stage_two_tea_cost: 2,
+                stage_three_tea_cost: 3,
+                stage_one_damage: 1.0,
+                stage_two_damage: 2.0,
+                stage_three_damage: 3.0,
+                stage_one_duration: 1.0,
+                stage_two_duration: 2.0,
+                stage_three_duration: 3.0,
+                time_between_hits: 0.5,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn complete_save() {
+        let (inventory, abilities, quests) = (inventory(TOTAL_KODAMAS), abilities(true), quests());
+        let (bosses, darumas) = (bosses(true), darumas(None));
+        let completion = Completion::new(&GameState {
+            inventory: Some(&inventory),
+            ability: Some(&abilities),
+            quest: Some(&quests),
+            bosses: Some(&bosses),
+            darumas: Some(&darumas),
+            ..Default::default()
+        });
+        assert_eq!(completion.parts.len(), PARTS);
+        assert!(completion.is_complete());
+        assert_eq!(completion.percent(), 100.0);
+        assert!(completion.missing().is_empty());
+    }
+
+    #[test]
+    fn every_part_has_to_be_read() {
+        let (inventory, abilities, quests) = (inventory(TOTAL_KODAMAS), abilities(true), quests());
+        let (bosses, darumas) = (bosses(true), darumas(None));
+        let state = GameState {
+            inventory: Some(&inventory),
+            ability: Some(&abilities),
+            quest: Some(&quests),
+            bosses: Some(&bosses),
+            darumas: Some(&darumas),
+            ..Default::default()
+        };
+        let unread = [
+            GameState {
+                inventory: None,
+                ..state
+            },
+            GameState {
+                ability: None,
+                ..state
+            },
+            GameState {
+                quest: None,
+                ..state
+            },
+            GameState {
+                bosses: None,
+                ..state
+            },
+            GameState {
+                darumas: None,
+                ..state
+            },
+        ];
+        for state in unread {
+            let completion = Completion::new(&state);
+            assert!(completion.parts.len() < PARTS);
+            assert!(!completion.is_complete());
+            assert_eq!(completion.percent(), 100.0);
+        }
+
+        let nothing = Completion::new(&GameState::default());
+        assert!(!nothing.is_complete());
+        assert_eq!(nothing.percent(), 0.0);
+    }
+
+    #[test]
+    fn names_what_is_missing() {
+        let (inventory, abilities, quests) = (inventory(32), abilities(false), quests());
+        let (bosses, darumas) = (bosses(false), darumas(Some(DarumaType::Ice)));
+        let completion = Completion::new(&GameState {
+            inventory: Some(&inventory),
+            ability: Some(&abilities),
+            quest: Some(&quests),
+            bosses: Some(&bosses),
+            darumas: Some(&darumas),
+            ..Default::default()
+        });
+        assert!(!completion.is_complete());
+        assert!(completion.percent() < 100.0);
+        assert_eq!(
+            completion.missing(),
+            vec![
+                "3 Kodamas".to_string(),
+                DarumaType::Ice.name().to_string(),
+                "Bat".to_string(),
+                "Karasu Tengu Duo".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_stay_in_range() {
+        assert_eq!(Part::counted("Kodamas", 40, TOTAL_KODAMAS).have, 35);
+        assert_eq!(Part::counted("Kodamas", -1, TOTAL_KODAMAS).have, 0);
+    }
+}
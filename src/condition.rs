@@ -0,0 +1,877 @@
+//! A small expression language for user-defined split conditions.
+//!
+//! Conditions are written against the fields of the bound game structs, for example `quest.fox_wedding_end && inventory.number_of_kodamas >= 20` or `boss[Hashihime].defeated && scene != "New Main Menu"`. A [`CustomSplit`] splits on the rising edge of its condition, so a condition that is already true when it is first evaluated (loading a save for example) does not split.
+
+use crate::game_data::{
+    AbilityManager, BetaPlayerDataManager, BossData, Daruma, DarumaManager, EnemiesManager,
+    GameManager, InventoryContainer, QuestManager,
+};
+
+/// The prefix of the settings keys that hold a custom split condition.
+pub const CUSTOM_SPLIT_PREFIX: &str = "custom_split";
+
+/// A value produced while evaluating a condition.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Num(f64),
+    Str(String),
+}
+
+impl Value {
+    fn truthy(&self) -> bool {
+        match self {
+            Value::Bool(b) => *b,
+            Value::Num(n) => *n != 0.0,
+            Value::Str(s) => !s.is_empty(),
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(n: i32) -> Self {
+        Value::Num(n as f64)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(n: f32) -> Self {
+        Value::Num(n as f64)
+    }
+}
+
+/// Generates a by-name field lookup for a bound struct, only the plain value fields are listed (pointers to other classes are not useful in a condition).
+macro_rules! condition_fields {
+    ($ty:ty { $($field:ident),* $(,)? }) => {
+        impl $ty {
+            pub const CONDITION_FIELDS: &'static [&'static str] = &[$(stringify!($field)),*];
+
+            pub fn field(&self, name: &str) -> Option<Value> {
+                match name {
+                    $(stringify!($field) => Some(self.$field.into()),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+condition_fields!(GameManager {
+    from_main_menu,
+    elevator_e_up,
+    elevator_1_up,
+    elevator_1_down,
+    elevator_2_up,
+    elevator_2_down,
+    elevator_3_up,
+    elevator_3_down,
+    vertical_chase_start,
+    load_game,
+    from_in_game,
+    is_quitting,
+    boss_percentage,
+});
+
+condition_fields!(QuestManager {
+    asahi_staff_start,
+    asahi_staff_end,
+    asahi_eye_of_beast_start,
+    asahi_eye_of_beast_end,
+    asahi_post_armapillo_boss,
+    tori_bump_told,
+    tori_bump_end,
+    tori_bat_told,
+    tori_bat_end,
+    tori_dash_told,
+    tori_dash_end,
+    shimeji_armapillos_collect,
+    shimeji_quest_start,
+    shimeji_quest_end,
+    rozus_requiem_start,
+    rozus_requiem_end,
+    fox_wedding_start,
+    fox_wedding_save_groom,
+    fox_wedding_end,
+    vermilion_stranger_quest_start,
+    vermilion_stranger_quest_end,
+    kitsune_kifuda_start,
+    kitsune_kifuda_end,
+    infinite_kettle_start,
+    infinite_kettle_end,
+    west_feather_in_keyhole,
+    east_feather_in_keyhole,
+    defeat_pua_boss,
+    defeat_hashihime_boss,
+    defeat_kaboto_boss,
+    defeat_spider_boss,
+    defeat_tengu_boss,
+    defeat_gash_boss,
+    defeat_asahi_boss,
+    defeat_sakura_boss,
+    credits_roll,
+});
+
+condition_fields!(AbilityManager {
+    can_attack,
+    can_bat,
+    can_dash,
+    can_hover,
+    can_idash,
+    can_grapple,
+    can_hammer_dash,
+    can_wall_jump,
+});
+
+condition_fields!(InventoryContainer {
+    feather_keys,
+    music_sheets,
+    omamori_straps,
+    fragile_egg,
+    reset_new_game,
+    has_kitsune_kifuda,
+    base_damage,
+    tablets,
+    number_of_kodamas,
+});
+
+condition_fields!(EnemiesManager { staff_damage });
+
+condition_fields!(DarumaManager {
+    daruma_boost_damage
+});
+
+condition_fields!(BetaPlayerDataManager { time_played });
+
+condition_fields!(BossData {
+    defeated,
+    in_progress,
+    total_health,
+    override_in_progress,
+});
+
+condition_fields!(Daruma {
+    available,
+    is_active,
+    two_eyes,
+    stage_one_tea_cost,
+    stage_two_tea_cost,
+    stage_three_tea_cost,
+    stage_one_damage,
+    stage_two_damage,
+    stage_three_damage,
+    stage_one_duration,
+    stage_two_duration,
+    stage_three_duration,
+    time_between_hits,
+});
+
+/// Everything a condition can look at, this is the last value read of each game object.
+#[derive(Default, Clone, Copy)]
+pub struct GameState<'a> {
+    pub scene: Option<&'a str>,
+    pub game: Option<&'a GameManager>,
+    pub quest: Option<&'a QuestManager>,
+    pub ability: Option<&'a AbilityManager>,
+    pub inventory: Option<&'a InventoryContainer>,
+    pub enemies: Option<&'a EnemiesManager>,
+    pub daruma_manager: Option<&'a DarumaManager>,
+    pub player: Option<&'a BetaPlayerDataManager>,
+    pub bosses: Option<&'a [BossData]>,
+    pub darumas: Option<&'a [Daruma]>,
+}
+
+/// The object a field is read from.
+#[derive(Debug, Clone, PartialEq)]
+enum Root {
+    Scene,
+    Game,
+    Quest,
+    Ability,
+    Inventory,
+    Enemies,
+    DarumaManager,
+    Player,
+    /// `boss[Name]`, matched against the `BossKind` name.
+    Boss(String),
+    /// `daruma[Name]`, matched against the `DarumaType` name.
+    Daruma(String),
+}
+
+impl Root {
+    fn fields(&self) -> &'static [&'static str] {
+        match self {
+            Root::Scene => &[],
+            Root::Game => GameManager::CONDITION_FIELDS,
+            Root::Quest => QuestManager::CONDITION_FIELDS,
+            Root::Ability => AbilityManager::CONDITION_FIELDS,
+            Root::Inventory => InventoryContainer::CONDITION_FIELDS,
+            Root::Enemies => EnemiesManager::CONDITION_FIELDS,
+            Root::DarumaManager => DarumaManager::CONDITION_FIELDS,
+            Root::Player => BetaPlayerDataManager::CONDITION_FIELDS,
+            Root::Boss(_) => BossData::CONDITION_FIELDS,
+            Root::Daruma(_) => Daruma::CONDITION_FIELDS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Lit(Value),
+    Field(Root, String),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Cmp(Box<Expr>, CmpOp, Box<Expr>),
+}
+
+impl Expr {
+    /// Returns `None` if a field could not be read (the object is not loaded yet).
+    fn eval(&self, state: &GameState<'_>) -> Option<Value> {
+        Some(match self {
+            Expr::Lit(val) => val.clone(),
+            Expr::Field(root, field) => match root {
+                Root::Scene => Value::Str(state.scene?.to_string()),
+                Root::Game => state.game?.field(field)?,
+                Root::Quest => state.quest?.field(field)?,
+                Root::Ability => state.ability?.field(field)?,
+                Root::Inventory => state.inventory?.field(field)?,
+                Root::Enemies => state.enemies?.field(field)?,
+                Root::DarumaManager => state.daruma_manager?.field(field)?,
+                Root::Player => state.player?.field(field)?,
+                Root::Boss(name) => state
+                    .bosses?
+                    .iter()
+                    .find(|b| format!("{:?}", b.boss_kind).eq_ignore_ascii_case(name))?
+                    .field(field)?,
+                Root::Daruma(name) => state
+                    .darumas?
+                    .iter()
+                    .find(|d| format!("{:?}", d.daruma_type).eq_ignore_ascii_case(name))?
+                    .field(field)?,
+            },
+            Expr::Not(e) => Value::Bool(!e.eval(state)?.truthy()),
+            Expr::And(l, r) => Value::Bool(l.eval(state)?.truthy() && r.eval(state)?.truthy()),
+            Expr::Or(l, r) => Value::Bool(l.eval(state)?.truthy() || r.eval(state)?.truthy()),
+            Expr::Cmp(l, op, r) => {
+                let ord = match (l.eval(state)?, r.eval(state)?) {
+                    (Value::Num(l), Value::Num(r)) => l.partial_cmp(&r),
+                    (Value::Str(l), Value::Str(r)) => Some(l.cmp(&r)),
+                    (Value::Bool(l), Value::Bool(r)) => Some(l.cmp(&r)),
+                    // Comparing different types is never true
+                    _ => None,
+                };
+                Value::Bool(ord.is_some_and(|ord| match op {
+                    CmpOp::Eq => ord.is_eq(),
+                    CmpOp::Ne => ord.is_ne(),
+                    CmpOp::Lt => ord.is_lt(),
+                    CmpOp::Le => ord.is_le(),
+                    CmpOp::Gt => ord.is_gt(),
+                    CmpOp::Ge => ord.is_ge(),
+                }))
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Num(f64),
+    Str(String),
+    Dot,
+    LBracket,
+    RBracket,
+    LParen,
+    RParen,
+    Not,
+    And,
+    Or,
+    Cmp(CmpOp),
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>, String> {
+    let chars = src.chars().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '.' => {
+                tokens.push(Token::Dot);
+                i += 1;
+            }
+            '[' => {
+                tokens.push(Token::LBracket);
+                i += 1;
+            }
+            ']' => {
+                tokens.push(Token::RBracket);
+                i += 1;
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            '&' if next == Some('&') => {
+                tokens.push(Token::And);
+                i += 2;
+            }
+            '|' if next == Some('|') => {
+                tokens.push(Token::Or);
+                i += 2;
+            }
+            '=' if next == Some('=') => {
+                tokens.push(Token::Cmp(CmpOp::Eq));
+                i += 2;
+            }
+            '!' if next == Some('=') => {
+                tokens.push(Token::Cmp(CmpOp::Ne));
+                i += 2;
+            }
+            '!' => {
+                tokens.push(Token::Not);
+                i += 1;
+            }
+            '<' if next == Some('=') => {
+                tokens.push(Token::Cmp(CmpOp::Le));
+                i += 2;
+            }
+            '<' => {
+                tokens.push(Token::Cmp(CmpOp::Lt));
+                i += 1;
+            }
+            '>' if next == Some('=') => {
+                tokens.push(Token::Cmp(CmpOp::Ge));
+                i += 2;
+            }
+            '>' => {
+                tokens.push(Token::Cmp(CmpOp::Gt));
+                i += 1;
+            }
+            '"' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|c| *c == '"')
+                    .ok_or_else(|| format!("unterminated string at {}", i))?;
+                tokens.push(Token::Str(chars[i + 1..i + 1 + end].iter().collect()));
+                i += end + 2;
+            }
+            _ if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let start = i;
+                i += 1;
+                while chars
+                    .get(i)
+                    .is_some_and(|c| c.is_ascii_digit() || *c == '.')
+                {
+                    i += 1;
+                }
+                let num = chars[start..i].iter().collect::<String>();
+                tokens.push(Token::Num(
+                    num.parse()
+                        .map_err(|_| format!("invalid number '{}'", num))?,
+                ));
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while chars
+                    .get(i)
+                    .is_some_and(|c| c.is_alphanumeric() || *c == '_')
+                {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            }
+            _ => return Err(format!("unexpected character '{}' at {}", c, i)),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let tok = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        tok
+    }
+
+    fn expect(&mut self, tok: Token) -> Result<(), String> {
+        match self.next() {
+            Some(t) if t == tok => Ok(()),
+            t => Err(format!("expected {:?} found {:?}", tok, t)),
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            lhs = Expr::Or(Box::new(lhs), Box::new(self.and()?));
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            lhs = Expr::And(Box::new(lhs), Box::new(self.unary()?));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::Not) {
+            self.next();
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        self.cmp()
+    }
+
+    fn cmp(&mut self) -> Result<Expr, String> {
+        let lhs = self.primary()?;
+        if let Some(Token::Cmp(op)) = self.peek().cloned() {
+            self.next();
+            return Ok(Expr::Cmp(Box::new(lhs), op, Box::new(self.primary()?)));
+        }
+        Ok(lhs)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::LParen) => {
+                let e = self.or()?;
+                self.expect(Token::RParen)?;
+                Ok(e)
+            }
+            Some(Token::Num(n)) => Ok(Expr::Lit(Value::Num(n))),
+            Some(Token::Str(s)) => Ok(Expr::Lit(Value::Str(s))),
+            Some(Token::Ident(id)) if id == "true" => Ok(Expr::Lit(Value::Bool(true))),
+            Some(Token::Ident(id)) if id == "false" => Ok(Expr::Lit(Value::Bool(false))),
+            Some(Token::Ident(id)) => self.path(id),
+            t => Err(format!("expected a value found {:?}", t)),
+        }
+    }
+
+    fn path(&mut self, root: String) -> Result<Expr, String> {
+        let root = match root.as_str() {
+            "scene" => return Ok(Expr::Field(Root::Scene, String::new())),
+            "game" => Root::Game,
+            "quest" => Root::Quest,
+            "ability" => Root::Ability,
+            "inventory" => Root::Inventory,
+            "enemies" => Root::Enemies,
+            "daruma_manager" => Root::DarumaManager,
+            "player" => Root::Player,
+            "boss" | "daruma" => {
+                self.expect(Token::LBracket)?;
+                let name = match self.next() {
+                    Some(Token::Ident(name)) | Some(Token::Str(name)) => name,
+                    t => return Err(format!("expected a {} name found {:?}", root, t)),
+                };
+                self.expect(Token::RBracket)?;
+                if root == "boss" {
+                    Root::Boss(name)
+                } else {
+                    Root::Daruma(name)
+                }
+            }
+            _ => return Err(format!("unknown object '{}'", root)),
+        };
+        self.expect(Token::Dot)?;
+        match self.next() {
+            Some(Token::Ident(field)) if root.fields().contains(&field.as_str()) => {
+                Ok(Expr::Field(root, field))
+            }
+            t => Err(format!("unknown field {:?} for {:?}", t, root)),
+        }
+    }
+}
+
+/// A parsed split condition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    source: String,
+    expr: Expr,
+}
+
+impl Condition {
+    pub fn parse(src: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            tokens: tokenize(src)?,
+            pos: 0,
+        };
+        let expr = parser.or()?;
+        if let Some(tok) = parser.peek() {
+            return Err(format!(
+                "unexpected {:?} after the end of the condition",
+                tok
+            ));
+        }
+        Ok(Self {
+            source: src.to_string(),
+            expr,
+        })
+    }
+
+    /// Returns `None` if something the condition reads is not available.
+    pub fn eval(&self, state: &GameState<'_>) -> Option<bool> {
+        self.expr.eval(state).map(|v| v.truthy())
+    }
+
+    /// The value of the condition itself, for a condition that is a single field.
+    pub fn value(&self, state: &GameState<'_>) -> Option<Value> {
+        self.expr.eval(state)
+    }
+
+    /// Returns true if the condition is a single field of a game object, like `inventory.tablets`.
+    pub fn is_field(&self) -> bool {
+        matches!(&self.expr, Expr::Field(root, _) if *root != Root::Scene)
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+/// A split that happens when its [`Condition`] goes from false to true.
+#[derive(Debug, Clone)]
+pub struct CustomSplit {
+    /// The key used in the settings map and the completed splits.
+    pub key: String,
+    pub condition: Condition,
+    last: Option<bool>,
+}
+
+impl CustomSplit {
+    pub fn new(key: String, condition: Condition) -> Self {
+        Self {
+            key,
+            condition,
+            last: None,
+        }
+    }
+
+    /// A segment name for the split, the key without the `custom_split` prefix (`fox` for `custom_split_fox`) or the condition when nothing is left.
+    pub fn name(&self) -> String {
+        let name = self
+            .key
+            .trim_start_matches(CUSTOM_SPLIT_PREFIX)
+            .replace('_', " ");
+        match name.trim() {
+            "" => self.condition.source().to_string(),
+            name => name.to_string(),
+        }
+    }
+
+    /// Evaluates the condition and returns true on the rising edge.
+    pub fn rising(&mut self, state: &GameState<'_>) -> bool {
+        let now = self.condition.eval(state);
+        let rising = self.last == Some(false) && now == Some(true);
+        self.last = now;
+        rising
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_data::{BossKind, DarumaType};
+
+    fn inventory(kodamas: i32) -> InventoryContainer {
+        InventoryContainer {
+            feather_keys: 1,
+            music_sheets: 0,
+            omamori_straps: 0,
+            fragile_egg: false,
+            reset_new_game: false,
+            has_kitsune_kifuda: true,
+            base_damage: 1.5,
+            tablets: 2,
+            number_of_kodamas: kodamas,
+        }
+    }
+
+    fn abilities(can_dash: bool) -> AbilityManager {
+        AbilityManager {
+            can_attack: true,
+            can_bat: false,
+            can_dash,
+            can_hover: false,
+            can_idash: false,
+            can_grapple: false,
+            can_hammer_dash: false,
+            can_wall_jump: false,
+        }
+    }
+
+    fn boss(boss_kind: BossKind, defeated: bool) -> BossData {
+        BossData {
+            boss_kind,
+            defeated,
+            in_progress: !defeated,
+            total_health: 133.0,
+            override_in_progress: false,
+        }
+    }
+
+    fn daruma(daruma_type: DarumaType, two_eyes: bool) -> Daruma {
+        Daruma {
+            daruma_type,
+            available: true,
+            is_active: false,
+            two_eyes,
+            stage_one_tea_cost: 1,
+            stage_two_tea_cost: 2,
+            stage_three_tea_cost: 3,
+            stage_one_damage: 1.0,
+            stage_two_damage: 2.0,
+            stage_three_damage: 3.0,
+            stage_one_duration: 1.0,
+            stage_two_duration: 2.0,
+            stage_three_duration: 3.0,
+            time_between_hits: 0.5,
+        }
+    }
+
+    fn eval(src: &str, state: &GameState<'_>) -> Option<bool> {
+        Condition::parse(src).unwrap().eval(state)
+    }
+
+    fn parse_err(src: &str) -> String {
+        Condition::parse(src).unwrap_err()
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let ability = abilities(false);
+        let state = GameState {
+            ability: Some(&ability),
+            ..Default::default()
+        };
+        // true || (false && false)
+        assert_eq!(
+            eval(
+                "ability.can_attack || ability.can_dash && ability.can_bat",
+                &state
+            ),
+            Some(true)
+        );
+        assert_eq!(
+            eval(
+                "(ability.can_attack || ability.can_dash) && ability.can_bat",
+                &state
+            ),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn not_applies_to_the_next_operand() {
+        let ability = abilities(false);
+        let state = GameState {
+            ability: Some(&ability),
+            ..Default::default()
+        };
+        assert_eq!(eval("!ability.can_dash", &state), Some(true));
+        assert_eq!(eval("!!ability.can_dash", &state), Some(false));
+        // (!can_dash) && can_attack, not !(can_dash && can_attack)
+        assert_eq!(
+            eval("!ability.can_dash && ability.can_attack", &state),
+            Some(true)
+        );
+        assert_eq!(
+            eval("!(ability.can_attack && ability.can_dash)", &state),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn compares_numbers() {
+        let inventory = inventory(20);
+        let state = GameState {
+            inventory: Some(&inventory),
+            ..Default::default()
+        };
+        assert_eq!(
+            eval("inventory.number_of_kodamas >= 20", &state),
+            Some(true)
+        );
+        assert_eq!(
+            eval("inventory.number_of_kodamas > 20", &state),
+            Some(false)
+        );
+        assert_eq!(eval("inventory.number_of_kodamas < 21", &state), Some(true));
+        assert_eq!(
+            eval("inventory.number_of_kodamas <= 19", &state),
+            Some(false)
+        );
+        assert_eq!(
+            eval("inventory.number_of_kodamas != 20", &state),
+            Some(false)
+        );
+        assert_eq!(eval("inventory.base_damage == 1.5", &state), Some(true));
+        assert_eq!(eval("inventory.tablets > -1", &state), Some(true));
+        // A number is true when it isn't 0
+        assert_eq!(eval("inventory.feather_keys", &state), Some(true));
+        assert_eq!(eval("inventory.music_sheets", &state), Some(false));
+    }
+
+    #[test]
+    fn compares_strings() {
+        let state = GameState {
+            scene: Some("CBF Intro"),
+            ..Default::default()
+        };
+        assert_eq!(eval("scene == \"CBF Intro\"", &state), Some(true));
+        assert_eq!(eval("scene != \"New Main Menu\"", &state), Some(true));
+        assert_eq!(eval("scene < \"D\"", &state), Some(true));
+        // Different types are never equal
+        assert_eq!(eval("scene == 1", &state), Some(false));
+        assert_eq!(eval("scene != 1", &state), Some(false));
+    }
+
+    #[test]
+    fn reads_bosses_and_darumas_by_name() {
+        let bosses = [
+            boss(BossKind::Hashihime, true),
+            boss(BossKind::KarasuTengu, false),
+        ];
+        let darumas = [
+            daruma(DarumaType::Bite, true),
+            daruma(DarumaType::Ice, false),
+        ];
+        let state = GameState {
+            bosses: Some(&bosses),
+            darumas: Some(&darumas),
+            ..Default::default()
+        };
+        assert_eq!(eval("boss[Hashihime].defeated", &state), Some(true));
+        assert_eq!(eval("boss[karasutengu].defeated", &state), Some(false));
+        assert_eq!(
+            eval("boss[\"KarasuTengu\"].total_health == 133", &state),
+            Some(true)
+        );
+        assert_eq!(
+            eval("daruma[Bite].two_eyes && !daruma[Ice].two_eyes", &state),
+            Some(true)
+        );
+        // A boss or daruma that isn't in the list can't be read
+        assert_eq!(eval("boss[Asahi].defeated", &state), None);
+        assert_eq!(eval("daruma[Bomb].available", &state), None);
+    }
+
+    #[test]
+    fn missing_objects_are_none() {
+        let state = GameState::default();
+        assert_eq!(eval("inventory.tablets > 1", &state), None);
+        assert_eq!(eval("!quest.fox_wedding_end", &state), None);
+        assert_eq!(eval("true || scene == \"CBF Intro\"", &state), Some(true));
+    }
+
+    #[test]
+    fn reports_errors() {
+        assert_eq!(parse_err("foo.bar"), "unknown object 'foo'");
+        assert_eq!(
+            parse_err("quest.nope"),
+            "unknown field Some(Ident(\"nope\")) for Quest"
+        );
+        assert_eq!(parse_err("scene == \"CBF"), "unterminated string at 9");
+        assert_eq!(parse_err("scene @ 1"), "unexpected character '@' at 6");
+        assert_eq!(
+            parse_err("boss[1].defeated"),
+            "expected a boss name found Some(Num(1.0))"
+        );
+        assert_eq!(parse_err("(ability.can_dash"), "expected RParen found None");
+        assert_eq!(
+            parse_err("ability.can_dash ability.can_bat"),
+            "unexpected Ident(\"ability\") after the end of the condition"
+        );
+        assert_eq!(parse_err(""), "expected a value found None");
+        assert_eq!(
+            parse_err("inventory.tablets >= 1.2.3"),
+            "invalid number '1.2.3'"
+        );
+    }
+
+    #[test]
+    fn is_field_only_for_a_single_game_field() {
+        assert!(Condition::parse("inventory.tablets").unwrap().is_field());
+        assert!(!Condition::parse("scene").unwrap().is_field());
+        assert!(!Condition::parse("inventory.tablets > 1")
+            .unwrap()
+            .is_field());
+    }
+
+    #[test]
+    fn custom_split_rises_once() {
+        let condition = Condition::parse("inventory.number_of_kodamas >= 3").unwrap();
+        let mut split = CustomSplit::new("custom_split_kodamas".to_string(), condition);
+        let (two, three) = (inventory(2), inventory(3));
+        let at = |inventory| GameState {
+            inventory: Some(inventory),
+            ..Default::default()
+        };
+        assert!(!split.rising(&at(&two)));
+        assert!(split.rising(&at(&three)));
+        assert!(!split.rising(&at(&three)));
+        assert!(!split.rising(&at(&two)));
+        assert!(split.rising(&at(&three)));
+    }
+
+    #[test]
+    fn custom_split_needs_a_false_read_first() {
+        let condition = Condition::parse("inventory.number_of_kodamas >= 3").unwrap();
+        let mut split = CustomSplit::new("custom_split_kodamas".to_string(), condition);
+        let (two, three) = (inventory(2), inventory(3));
+        let at = |inventory| GameState {
+            inventory: Some(inventory),
+            ..Default::default()
+        };
+        // Already true on the first read, like loading a save
+        assert!(!split.rising(&at(&three)));
+        // A read that fails resets the edge, so the next true doesn't split
+        assert!(!split.rising(&at(&two)));
+        assert!(!split.rising(&GameState::default()));
+        assert!(!split.rising(&at(&three)));
+    }
+
+    #[test]
+    fn custom_split_names() {
+        let condition = Condition::parse("quest.fox_wedding_end").unwrap();
+        let named = CustomSplit::new("custom_split_fox_wedding".to_string(), condition.clone());
+        assert_eq!(named.name(), "fox wedding");
+        let unnamed = CustomSplit::new(CUSTOM_SPLIT_PREFIX.to_string(), condition);
+        assert_eq!(unnamed.name(), "quest.fox_wedding_end");
+    }
+}
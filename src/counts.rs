@@ -0,0 +1,351 @@
+//! Splits when a count in the game reaches a threshold, like the number of Kodamas.
+
+use asr::settings::Map;
+
+use crate::{
+    condition::{Condition, GameState, Value},
+    lss::Setting,
+    settings::Settings,
+};
+
+/// The `.lss` setting with the Kodama counts to split at when the Kodama setting is "Custom", a string like `3,8,15,35` or a list with an entry per count.
+pub const KODAMA_THRESHOLDS_SETTING: &str = "kodama_thresholds";
+
+/// The `.lss` setting with the thresholds of any other count, a string like `inventory.tablets: 1,3,5; quest.shimeji_armapillos_collect: 2,4` or a list with an entry per count.
+pub const COUNT_SPLITS_SETTING: &str = "count_splits";
+
+const KODAMAS_FIELD: &str = "inventory.number_of_kodamas";
+
+/// Parses a count to split at, counts start at 1.
+fn parse_threshold(src: &str) -> Result<i32, String> {
+    match src.trim().parse::<i32>() {
+        Ok(n) if n > 0 => Ok(n),
+        Ok(_) => Err(format!("'{}': counts start at 1", src.trim())),
+        Err(e) => Err(format!("'{}': {}", src.trim(), e)),
+    }
+}
+
+/// Parses a comma separated list of counts, sorted and without repeats.
+fn parse_thresholds(src: &str) -> Result<Vec<i32>, String> {
+    let mut thresholds = src
+        .split(',')
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(parse_threshold)
+        .collect::<Result<Vec<_>, _>>()?;
+    if thresholds.is_empty() {
+        return Err("no counts to split at".to_string());
+    }
+    thresholds.sort_unstable();
+    thresholds.dedup();
+    Ok(thresholds)
+}
+
+/// The segment name of the split at `count` of the count called `name`.
+pub fn count_label(name: &str, count: &str) -> String {
+    match name {
+        "number_of_kodamas" => format!("{} Kodamas", count),
+        "damage_upgrades" => format!("Damage Upgrade {}", count),
+        _ => format!("{} {}", name, count),
+    }
+}
+
+/// Splits when an integer field goes up past one of its thresholds.
+#[derive(Debug, Clone)]
+pub struct CountSplit {
+    /// The field name, completed splits are kept as `{name}__{count}`.
+    pub name: String,
+    field: Condition,
+    pub thresholds: Vec<i32>,
+    last: Option<i32>,
+}
+
+impl CountSplit {
+    /// `path` is a field as written in a condition, `inventory.tablets`.
+    pub fn new(path: &str, thresholds: Vec<i32>) -> Result<Self, String> {
+        let path = path.trim();
+        let field = Condition::parse(path)?;
+        if !field.is_field() {
+            return Err(format!("'{}' is not a field", path));
+        }
+        Ok(Self {
+            name: path.rsplit('.').next().unwrap_or(path).to_string(),
+            field,
+            thresholds,
+            last: None,
+        })
+    }
+
+    /// Parses a `path: thresholds` entry.
+    pub fn parse(entry: &str) -> Result<Self, String> {
+        let (path, thresholds) = entry
+            .split_once(':')
+            .ok_or_else(|| format!("expected `field: 1,2,3` found '{}'", entry))?;
+        Self::new(path, parse_thresholds(thresholds)?)
+    }
+
+    pub fn key(&self, count: i32) -> String {
+        format!("{}__{}", self.name, count)
+    }
+
+    pub fn label(&self, count: i32) -> String {
+        count_label(&self.name, &count.to_string())
+    }
+
+    /// Reads the count and returns the thresholds it went past since the last read, a count that can't be read or the first read splits nothing.
+    pub fn crossed(&mut self, state: &GameState<'_>) -> Vec<i32> {
+        let now = match self.field.value(state) {
+            Some(Value::Num(n)) => Some(n as i32),
+            _ => None,
+        };
+        let crossed = match (self.last, now) {
+            (Some(last), Some(now)) => self
+                .thresholds
+                .iter()
+                .copied()
+                .filter(|t| last < *t && *t <= now)
+                .collect(),
+            _ => vec![],
+        };
+        self.last = now;
+        crossed
+    }
+}
+
+/// The Kodama splits from the settings and the other count splits from the `.lss`.
+#[derive(Debug, Clone)]
+pub struct CountSplits {
+    pub kodamas: CountSplit,
+    /// The `kodama_thresholds` of the `.lss`.
+    custom_kodamas: Vec<i32>,
+    pub others: Vec<CountSplit>,
+}
+
+impl Default for CountSplits {
+    fn default() -> Self {
+        Self {
+            kodamas: CountSplit::new(KODAMAS_FIELD, vec![]).expect("a known field"),
+            custom_kodamas: vec![],
+            others: vec![],
+        }
+    }
+}
+
+impl CountSplits {
+    /// Reads the threshold lists of the `.lss` settings, returning an error for every entry that can't be read (those are left out).
+    pub fn load(&mut self, map: &Map) -> Vec<String> {
+        let setting = |key| map.get(key).and_then(|value| Setting::from_value(&value));
+        self.read(
+            setting(KODAMA_THRESHOLDS_SETTING).as_ref(),
+            setting(COUNT_SPLITS_SETTING).as_ref(),
+        )
+    }
+
+    /// [`Self::load`] from the `kodama_thresholds` and `count_splits` settings.
+    fn read(&mut self, kodamas: Option<&Setting>, counts: Option<&Setting>) -> Vec<String> {
+        let mut errors = vec![];
+        let entries = |setting: Option<&Setting>, sep| setting.map_or(vec![], |s| s.entries(sep));
+
+        self.custom_kodamas.clear();
+        for entry in entries(kodamas, ',') {
+            match entry.and_then(|entry| parse_threshold(&entry)) {
+                Ok(n) => self.custom_kodamas.push(n),
+                Err(err) => errors.push(format!("{}: {}", KODAMA_THRESHOLDS_SETTING, err)),
+            }
+        }
+        self.custom_kodamas.sort_unstable();
+        self.custom_kodamas.dedup();
+
+        self.others.clear();
+        for entry in entries(counts, ';') {
+            match entry.and_then(|entry| CountSplit::parse(&entry)) {
+                Ok(count) => self.others.push(count),
+                Err(err) => errors.push(format!("{}: {}", COUNT_SPLITS_SETTING, err)),
+            }
+        }
+        errors
+    }
+
+    /// Sets the Kodama thresholds from the settings, which can change at any time.
+    pub fn update(&mut self, settings: &Settings) {
+        self.kodamas.thresholds = settings.kodama_thresholds(&self.custom_kodamas);
+    }
+
+    /// Every count, counts keep being read while their splits are off so turning them on doesn't split for what was collected in between.
+    pub fn all_mut(&mut self) -> impl Iterator<Item = &mut CountSplit> {
+        std::iter::once(&mut self.kodamas).chain(self.others.iter_mut())
+    }
+
+    /// The segment names of every enabled count split, the other counts are collectible splits.
+    pub fn labels(&self, settings: &Settings) -> Vec<String> {
+        let others = if settings.collectible_splits {
+            &self.others[..]
+        } else {
+            &[]
+        };
+        std::iter::once(&self.kodamas)
+            .chain(others)
+            .flat_map(|count| count.thresholds.iter().map(|n| count.label(*n)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_data::InventoryContainer;
+
+    fn inventory(kodamas: i32) -> InventoryContainer {
+        InventoryContainer {
+            feather_keys: 0,
+            music_sheets: 0,
+            omamori_straps: 0,
+            fragile_egg: false,
+            reset_new_game: false,
+            has_kitsune_kifuda: false,
+            base_damage: 1.0,
+            tablets: 0,
+            number_of_kodamas: kodamas,
+        }
+    }
+
+    fn crossed(count: &mut CountSplit, kodamas: Option<i32>) -> Vec<i32> {
+        let inventory = kodamas.map(inventory);
+        count.crossed(&GameState {
+            inventory: inventory.as_ref(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn parses_thresholds() {
+        assert_eq!(
+            parse_thresholds(" 15, 3,8 ,3,, 35 "),
+            Ok(vec![3, 8, 15, 35])
+        );
+        assert_eq!(
+            parse_thresholds("3, 0"),
+            Err("'0': counts start at 1".to_string())
+        );
+        assert!(parse_thresholds("3, three").is_err());
+        assert_eq!(
+            parse_thresholds(" , "),
+            Err("no counts to split at".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_count_splits() {
+        let count = CountSplit::parse(" inventory.tablets : 5, 1,3").unwrap();
+        assert_eq!(count.name, "tablets");
+        assert_eq!(count.thresholds, vec![1, 3, 5]);
+        assert_eq!(count.key(3), "tablets__3");
+        assert_eq!(count.label(3), "tablets 3");
+        assert_eq!(
+            CountSplit::new(KODAMAS_FIELD, vec![]).unwrap().label(35),
+            "35 Kodamas"
+        );
+        assert!(CountSplit::parse("inventory.tablets 1,3").is_err());
+        assert!(CountSplit::parse("inventory.tablets:").is_err());
+        assert_eq!(
+            CountSplit::parse("inventory.tablets > 1: 1").unwrap_err(),
+            "'inventory.tablets > 1' is not a field"
+        );
+    }
+
+    #[test]
+    fn first_read_splits_nothing() {
+        let mut count = CountSplit::new(KODAMAS_FIELD, vec![3, 8]).unwrap();
+        assert_eq!(crossed(&mut count, Some(10)), vec![]);
+        assert_eq!(crossed(&mut count, Some(10)), vec![]);
+    }
+
+    #[test]
+    fn splits_every_threshold_passed() {
+        let mut count = CountSplit::new(KODAMAS_FIELD, vec![3, 8, 15, 35]).unwrap();
+        crossed(&mut count, Some(0));
+        assert_eq!(crossed(&mut count, Some(2)), vec![]);
+        assert_eq!(crossed(&mut count, Some(3)), vec![3]);
+        assert_eq!(crossed(&mut count, Some(40)), vec![8, 15, 35]);
+        // Going back down and up again splits again, the completed splits keep it from splitting twice
+        assert_eq!(crossed(&mut count, Some(2)), vec![]);
+        assert_eq!(crossed(&mut count, Some(4)), vec![3]);
+    }
+
+    #[test]
+    fn unreadable_count_splits_nothing() {
+        let mut count = CountSplit::new(KODAMAS_FIELD, vec![3, 8]).unwrap();
+        crossed(&mut count, Some(0));
+        assert_eq!(crossed(&mut count, None), vec![]);
+        // The read after the gap is a first read again
+        assert_eq!(crossed(&mut count, Some(5)), vec![]);
+        assert_eq!(crossed(&mut count, Some(8)), vec![8]);
+    }
+
+    #[test]
+    fn loads_string_settings() {
+        let mut counts = CountSplits::default();
+        let errors = counts.read(
+            Some(&Setting::String("8, 3,, 3, 0, x".to_string())),
+            Some(&Setting::String(
+                "inventory.tablets: 1,3; ; quest.shimeji_armapillos_collect: 2,4; inventory.tablets"
+                    .to_string(),
+            )),
+        );
+        assert_eq!(counts.custom_kodamas, vec![3, 8]);
+        let others = counts
+            .others
+            .iter()
+            .map(|count| (count.name.as_str(), count.thresholds.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            others,
+            vec![
+                ("tablets", vec![1, 3]),
+                ("shimeji_armapillos_collect", vec![2, 4]),
+            ]
+        );
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0].starts_with("kodama_thresholds: '0'"));
+        assert!(errors[1].starts_with("kodama_thresholds: 'x'"));
+        assert!(errors[2].starts_with("count_splits: expected"));
+    }
+
+    #[test]
+    fn loads_list_settings() {
+        let mut counts = CountSplits::default();
+        let errors = counts.read(
+            Some(&Setting::List(vec![
+                Setting::I64(15),
+                Setting::String(" 3 ".to_string()),
+                Setting::Bool(true),
+            ])),
+            Some(&Setting::List(vec![Setting::String(
+                "inventory.tablets: 5".to_string(),
+            )])),
+        );
+        assert_eq!(counts.custom_kodamas, vec![3, 15]);
+        assert_eq!(counts.others.len(), 1);
+        assert_eq!(counts.others[0].thresholds, vec![5]);
+        assert_eq!(
+            errors,
+            vec!["kodama_thresholds: has an item that isn't text".to_string()]
+        );
+    }
+
+    #[test]
+    fn loading_replaces_the_last_settings() {
+        let mut counts = CountSplits::default();
+        counts.read(
+            Some(&Setting::String("3".to_string())),
+            Some(&Setting::String("inventory.tablets: 5".to_string())),
+        );
+        let errors = counts.read(None, Some(&Setting::Bool(true)));
+        assert!(counts.custom_kodamas.is_empty());
+        assert!(counts.others.is_empty());
+        assert_eq!(
+            errors,
+            vec!["count_splits: must be a string or list setting".to_string()]
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! Timer variables for text components in a layout, each is only sent when its value changes.
+
+use std::collections::{HashMap, HashSet};
+
+use asr::timer;
+
+use crate::{
+    boss::BOSS_HEALTH_PREFIX,
+    completion::{
+        Completion, TOTAL_FEATHER_KEYS, TOTAL_KODAMAS, TOTAL_MUSIC_SHEETS, TOTAL_OMAMORI_STRAPS,
+        TOTAL_VS_TABLETS,
+    },
+    condition::GameState,
+    counts::count_label,
+    game_data::DarumaType,
+    lss::Segment,
+    route::Route,
+    settings::split_info,
+};
+
+#[derive(Debug, Default)]
+pub struct Dashboard {
+    /// The last value sent of every variable.
+    sent: HashMap<&'static str, String>,
+    /// The splits taken this run, to find the one taken last.
+    taken: HashSet<String>,
+}
+
+impl Dashboard {
+    /// Sets the variable `name`, LiveSplit is only told when the value changed.
+    pub fn set(&mut self, name: &'static str, value: &str) {
+        if self.sent.get(name).map(String::as_str) != Some(value) {
+            timer::set_variable(name, value);
+            self.sent.insert(name, value.to_string());
+        }
+    }
+
+    /// Publishes the scene and the collected items, abilities and darumas.
+    pub fn update_progress(&mut self, state: &GameState) {
+        if let Some(scene) = state.scene {
+            self.set("Scene", scene);
+        }
+
+        if let Some(inventory) = state.inventory {
+            let out_of = |n: i32, total: i32| format!("{}/{}", n, total);
+            self.set(
+                "Kodamas",
+                &out_of(inventory.number_of_kodamas, TOTAL_KODAMAS),
+            );
+            self.set("VS tablets", &out_of(inventory.tablets, TOTAL_VS_TABLETS));
+            self.set(
+                "Feather keys",
+                &out_of(inventory.feather_keys, TOTAL_FEATHER_KEYS),
+            );
+            self.set(
+                "Music sheets",
+                &out_of(inventory.music_sheets, TOTAL_MUSIC_SHEETS),
+            );
+            self.set(
+                "Omamori straps",
+                &out_of(inventory.omamori_straps, TOTAL_OMAMORI_STRAPS),
+            );
+        }
+
+        if let Some(ability) = state.ability {
+            let abilities = [
+                ability.can_attack,
+                ability.can_bat,
+                ability.can_dash,
+                ability.can_hover,
+                ability.can_idash,
+                ability.can_grapple,
+                ability.can_hammer_dash,
+                ability.can_wall_jump,
+            ];
+            let unlocked = abilities.iter().filter(|can| **can).count();
+            self.set("Abilities", &format!("{}/{}", unlocked, abilities.len()));
+        }
+
+        if let Some(darumas) = state.darumas {
+            // Two of the types are never given to the player
+            let darumas = darumas
+                .iter()
+                .filter(|d| {
+                    !matches!(
+                        d.daruma_type,
+                        DarumaType::SpinAttack | DarumaType::Deprecated1
+                    )
+                })
+                .collect::<Vec<_>>();
+            let owned = darumas.iter().filter(|d| d.available).count();
+            self.set("Darumas", &format!("{}/{}", owned, darumas.len()));
+
+            let equipped = darumas
+                .iter()
+                .filter(|d| d.is_active)
+                .map(|d| d.daruma_type.name())
+                .collect::<Vec<_>>();
+            let equipped = if equipped.is_empty() {
+                "None".to_string()
+            } else {
+                equipped.join(", ")
+            };
+            self.set("Darumas equipped", &equipped);
+        }
+    }
+
+    /// Publishes the completion percentage and what is still missing for 100%.
+    pub fn update_completion(&mut self, completion: &Completion) {
+        self.set("Completion", &format!("{:.0}%", completion.percent()));
+        let missing = completion.missing();
+        self.set(
+            "Missing",
+            &if missing.is_empty() {
+                "-".to_string()
+            } else {
+                missing.join(", ")
+            },
+        );
+    }
+
+    /// Publishes the last split taken and the next one expected, from the route when there is one or else the segments of the splits file.
+    pub fn update_splits(
+        &mut self,
+        completed: &HashMap<String, bool>,
+        route: &Route,
+        segments: &[Segment],
+    ) {
+        let taken = completed
+            .iter()
+            .filter(|(_, done)| **done)
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+        // The completed splits are cleared for a new run
+        if taken.len() < self.taken.len() {
+            self.taken.clear();
+            self.set("Last split", "-");
+        }
+        let mut last = None;
+        for key in taken {
+            if self.taken.insert(key.clone()) {
+                last = Some(key);
+            }
+        }
+        if let Some(last) = last {
+            self.set("Last split", &split_label(last, route));
+        }
+
+        let next = if route.splits.is_empty() {
+            segments.get(self.taken.len()).map(|s| s.name.clone())
+        } else {
+            route
+                .splits
+                .iter()
+                .find(|s| !completed.get(&s.id).copied().unwrap_or(false))
+                .map(|s| s.label.clone())
+        };
+        self.set("Next split", next.as_deref().unwrap_or("-"));
+    }
+}
+
+/// A readable name for the split `key`.
+fn split_label(key: &str, route: &Route) -> String {
+    if let Some(split) = route.splits.iter().find(|s| s.id == key) {
+        return split.label.clone();
+    }
+    if let Some(info) = split_info(key) {
+        return info.name.to_string();
+    }
+    match key.rsplit_once("__") {
+        Some((name, n)) if !key.starts_with(BOSS_HEALTH_PREFIX) => count_label(name, n),
+        _ => key.to_string(),
+    }
+}
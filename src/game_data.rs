@@ -5,24 +5,99 @@ use bytemuck::{Pod, Zeroable};
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(u32)]
 pub enum BossKind {
+    /// Unused
     Placeholder = 0,
+    /// KiriKiri Bozu, the first boss
     KiriKiriBozu = 1,
+    /// Particularly Unmanageable Armadillo
     PUA = 2,
+    /// Hashihime (the bridge)
     Hashihime = 3,
+    /// Yuki, tracked like the other bosses, where the fight happens isn't known yet
     Yuki = 4,
+    /// Yokozuna Kaboto (the beetle)
     Yokozuna = 5,
+    /// Jorogumo (the spider)
     Jorogumo = 6,
+    /// Karasu Tengu, both the single (133 health) and the duo (225 health) fights
     KarasuTengu = 7,
+    /// Dai Tengu (the Tengu Trio)
     DaiTengu = 8,
+    /// Gashadokuro (the giant skeleton)
     Gasha = 9,
+    /// Asahi
     Asahi = 10,
+    /// Sakura Shogun, the final boss
     Shogun = 11,
+    /// Amaterasu, tracked like the other bosses, where the fight happens isn't known yet
     Amaterasu = 12,
 }
 
 unsafe impl Zeroable for BossKind {}
 unsafe impl Pod for BossKind {}
 
+impl BossKind {
+    pub const ALL: [BossKind; 13] = [
+        BossKind::Placeholder,
+        BossKind::KiriKiriBozu,
+        BossKind::PUA,
+        BossKind::Hashihime,
+        BossKind::Yuki,
+        BossKind::Yokozuna,
+        BossKind::Jorogumo,
+        BossKind::KarasuTengu,
+        BossKind::DaiTengu,
+        BossKind::Gasha,
+        BossKind::Asahi,
+        BossKind::Shogun,
+        BossKind::Amaterasu,
+    ];
+
+    /// Returns false for the placeholder and the bosses whose fight isn't placed in a run yet, these are left out of the completion and of the health splits of every boss.
+    pub fn has_fight(self) -> bool {
+        !matches!(
+            self,
+            BossKind::Placeholder | BossKind::Yuki | BossKind::Amaterasu
+        )
+    }
+
+    /// The number of fights against this boss in a run, Karasu Tengu is fought alone and as a duo.
+    pub fn fights(self) -> usize {
+        match self {
+            BossKind::KarasuTengu => 2,
+            _ => 1,
+        }
+    }
+
+    /// The name the game shows for this boss.
+    pub fn name(self) -> &'static str {
+        match self {
+            BossKind::Placeholder => "Placeholder",
+            BossKind::KiriKiriBozu => "KiriKiri Bozu",
+            BossKind::PUA => "Particularly Unmanageable Armadillo",
+            BossKind::Hashihime => "Hashihime",
+            BossKind::Yuki => "Yuki",
+            BossKind::Yokozuna => "Yokozuna Kaboto",
+            BossKind::Jorogumo => "Jorogumo",
+            BossKind::KarasuTengu => "Karasu Tengu",
+            BossKind::DaiTengu => "Dai Tengu",
+            BossKind::Gasha => "Gashadokuro",
+            BossKind::Asahi => "Asahi",
+            BossKind::Shogun => "Sakura Shogun",
+            BossKind::Amaterasu => "Amaterasu",
+        }
+    }
+
+    /// Finds a boss by its variant (`Shogun`) or shown name (`Sakura Shogun`), ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim();
+        BossKind::ALL.into_iter().find(|kind| {
+            format!("{:?}", kind).eq_ignore_ascii_case(name)
+                || kind.name().eq_ignore_ascii_case(name)
+        })
+    }
+}
+
 #[derive(Debug, Class, Copy, Clone, PartialEq, PartialOrd)]
 pub struct BossData {
     #[rename = "<Boss>k__BackingField"]
@@ -74,6 +149,24 @@ pub enum DarumaType {
 unsafe impl Zeroable for DarumaType {}
 unsafe impl Pod for DarumaType {}
 
+impl DarumaType {
+    /// The name the game shows for this daruma.
+    pub fn name(self) -> &'static str {
+        match self {
+            DarumaType::Bite => "Chomper",
+            DarumaType::Parry => "Mamori",
+            DarumaType::Thorns => "TogiChan",
+            DarumaType::Spirits => "Jingu",
+            DarumaType::Bomb => "Kaboomaru",
+            DarumaType::SpinAttack => "Spin Attack",
+            DarumaType::Deprecated1 => "Deprecated",
+            DarumaType::FireWall => "PyroKun",
+            DarumaType::Ice => "Yuki",
+            DarumaType::Boomerang => "Ken",
+        }
+    }
+}
+
 #[derive(Debug, Class, Copy, Clone, PartialEq, PartialOrd)]
 pub struct Daruma {
     #[rename = "<Type>k__BackingField"]
@@ -197,28 +290,28 @@ pub struct QuestManager {
     #[rename = "<AsahiEyeOfTheBeastQuestCompleted>k__BackingField"]
     pub asahi_eye_of_beast_end: bool,
 
-    /// Not really sure when this splits
+    /// Asahi talks to you after the Particularly Unmanageable Armadillo (armapillo) boss is beaten
     #[rename = "<AsahiAfterArmapilloBoss>k__BackingField"]
     pub asahi_post_armapillo_boss: bool,
 
-    // No idea what bump (start)
+    /// Tori (the bird) tells the prophecy of the bump
     #[rename = "<ToriBumpProphecyTold>k__BackingField"]
     pub tori_bump_told: bool,
-    // No idea what bump (end)
+    /// The bump prophecy is fulfilled
     #[rename = "<ToriFulfilledBumpProphecy>k__BackingField"]
     pub tori_bump_end: bool,
 
-    /// Bird bat prophecy start (not really sure)
+    /// Tori tells the prophecy of the bat (the staff swing given after KiriKiri Bozu)
     #[rename = "<ToriBatProphecyTold>k__BackingField"]
     pub tori_bat_told: bool,
-    /// Bird bat prophecy start (not really sure)
+    /// The bat prophecy is fulfilled, you come back to Tori with the bat ability
     #[rename = "<ToriFulfilledBatProphecy>k__BackingField"]
     pub tori_bat_end: bool,
 
-    /// Bird dash prophecy start (not really sure)
+    /// Tori tells the prophecy of the dash (given with the Eye of the Beast)
     #[rename = "<ToriDashProphecyTold>k__BackingField"]
     pub tori_dash_told: bool,
-    /// Bird dash prophecy end (not really sure)
+    /// The dash prophecy is fulfilled, you come back to Tori with the dash ability
     #[rename = "<ToriFulfilledDashProphecy>k__BackingField"]
     pub tori_dash_end: bool,
 
@@ -256,10 +349,10 @@ pub struct QuestManager {
     #[rename = "<VSQuestCompleted>k__BackingField"]
     pub vermilion_stranger_quest_end: bool,
 
-    /// The Kitsune Kifuda quest (DaiTangu and Gashadoku)
+    /// The Kitsune Kifuda quest, the same flag as the fox wedding quest
     #[rename = "<KitsuneKifudaQuestStarted>k__BackingField"]
     pub kitsune_kifuda_start: bool,
-    /// The Kitsune Kifuda quest (DaiTangu and Gashadoku)
+    /// The Kitsune Kifuda quest, the same flag as the fox wedding quest
     #[rename = "<KitsuneKifudaQuestCompleted>k__BackingField"]
     pub kitsune_kifuda_end: bool,
 
@@ -359,3 +452,20 @@ pub struct GameManager {
     #[rename = "darumaManager"]
     pub daruma_pointer: Address64,
 }
+
+impl GameManager {
+    /// Returns true when any palace elevator was taken since `old`.
+    pub fn elevator_taken(&self, old: &GameManager) -> bool {
+        [
+            (old.elevator_e_up, self.elevator_e_up),
+            (old.elevator_1_up, self.elevator_1_up),
+            (old.elevator_2_up, self.elevator_2_up),
+            (old.elevator_3_up, self.elevator_3_up),
+            (old.elevator_1_down, self.elevator_1_down),
+            (old.elevator_2_down, self.elevator_2_down),
+            (old.elevator_3_down, self.elevator_3_down),
+        ]
+        .iter()
+        .any(|(old, new)| !old && *new)
+    }
+}
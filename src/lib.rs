@@ -1,6 +1,11 @@
 #![feature(type_alias_impl_trait, const_async_blocks, wasi_ext)]
 
-use std::{collections::HashMap, fs::File, io::BufReader};
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{BufReader, Write},
+    path::Path,
+};
 
 use asr::{
     future::next_tick,
@@ -10,19 +15,41 @@ use asr::{
     timer::{self, TimerState},
     Address64, Process,
 };
-use xml::{reader::XmlEvent as ReaderEvent, EventReader};
+use xml::EventReader;
 
 mod array;
+mod boss;
+mod completion;
+mod condition;
+mod counts;
+mod dashboard;
 mod game_data;
+mod lss;
+mod route;
 mod settings;
+mod upgrades;
 
 use array::CSharpArray;
+use boss::{fight_in_progress, BossFight, HealthSplits, RunStats, BOSS_HEALTH_PREFIX};
+use completion::Completion;
+use condition::{Condition, CustomSplit, GameState, CUSTOM_SPLIT_PREFIX};
+use counts::CountSplits;
+use dashboard::Dashboard;
 use game_data::{
     AbilityManager, BetaPlayerDataManager, BossData, BossDataBinding, BossKind, Daruma,
     DarumaBinding, DarumaManager, DarumaType, EnemiesManager, GameManager, InventoryContainer,
     QuestManager,
 };
-use settings::{NumberOfKodamas, Settings};
+use lss::{
+    auto_map, exported_settings, patch_lss, validate, write_lss, write_settings_xml, FileVersion,
+    Segment, SegmentReader, SettingsReader, SplitFile,
+};
+use route::Route;
+use settings::{
+    split_info, Category, ExportSettings, GenerateSplitsFile, Settings, SplitInfo, ANY_PERCENT,
+    HUNDRED_PERCENT, SETTING_ALIASES, SPLITS,
+};
+use upgrades::{DamageKind, DamageUpgrades};
 
 asr::async_main!(stable);
 
@@ -33,12 +60,15 @@ fn print_message(msg: &str) {
 
 async fn main() {
     // TODO: Set up some general state and settings.
+    // Before registering so the settings see the migrated values
+    migrate_settings_map();
     let mut settings = Settings::register();
 
     print_message("Bo AutoSplitter ON!!");
-    let mut old_setting_file = None;
-    let mut completed_splits = HashMap::new();
-    update_settings(&mut settings, &mut old_setting_file, &mut completed_splits);
+    let mut splits = SplitState::default();
+    let mut run_stats = RunStats::default();
+    let mut damage_upgrades = DamageUpgrades::default();
+    update_settings(&mut settings, &mut splits);
 
     loop {
         let process = Process::wait_attach("Bo.exe").await;
@@ -56,6 +86,7 @@ async fn main() {
                         print_message("Attached SceneManager");
 
                         let mut old_scene_name = None;
+                        let mut chase_running = false;
                         if let Ok(path) = scene_manager.get_current_scene_path::<128>(&process) {
                             let name =
                                 String::from_utf8_lossy(get_scene_name(path.as_bytes())).to_string();
@@ -145,6 +176,10 @@ async fn main() {
                             bl.sort_by_key(|b| b.boss_kind);
                             print_message(&format!("got Boss list: {:#?}", bl));
                         }
+                        let mut boss_fight = BossFight::default();
+                        // The time played when the practice fight started, game time counts from here
+                        let mut practice_start = 0.0;
+                        let mut completion = Completion::default();
 
                         let daruma_class = Daruma::bind(&process, &module, &img).await;
                         let daruma_manager = DarumaManager::bind(&process, &module, &img).await;
@@ -169,12 +204,20 @@ async fn main() {
                         'normal_game_loop: loop {
                             // Check for a complete run to reset the completed splits map
                             match timer::state() {
-                                TimerState::NotRunning
-                                | TimerState::Running
+                                TimerState::NotRunning => {
+                                    // A reset run doesn't get a summary
+                                    run_stats.clear();
+                                    damage_upgrades.clear();
+                                },
+                                TimerState::Running
                                 | TimerState::Paused => {},
                                 TimerState::Ended => {
+                                    if !run_stats.is_empty() {
+                                        report_run_stats(&settings, &run_stats);
+                                        run_stats.clear();
+                                    }
                                     // reset completed splits and any other settings
-                                    completed_splits.clear();
+                                    splits.completed.clear();
                                     paused = false;
                                 },
                                 TimerState::Unknown => {
@@ -184,7 +227,7 @@ async fn main() {
                                 _ => {}
                             }
                             // This checks for on the fly updates to the settings (you could add a split mid run)
-                            update_settings(&mut settings, &mut old_setting_file, &mut completed_splits);
+                            update_settings(&mut settings, &mut splits);
 
                             // UPDATE first since this knows about everything
                             match game_manager_class.read(&process, game_manager_inst) {
@@ -226,13 +269,10 @@ async fn main() {
                                                 Some(false)
                                                     if game_manager.$field
                                                         && settings.$field
-                                                        && !completed_splits
-                                                            .get(stringify!($field))
-                                                            .copied()
-                                                            .unwrap_or(true) =>
+                                                        && split_ready(stringify!($field), &settings, &splits.completed, &splits.route) =>
                                                 {
                                                     print_message(concat!("Split for ", $msg));
-                                                    *completed_splits
+                                                    *splits.completed
                                                         .entry(stringify!($field).to_string())
                                                         .or_insert(true) = true;
 
@@ -252,6 +292,62 @@ async fn main() {
                                     check_game_manager!(elevator_2_up, "second floor elevator up");
                                     // Third floor elevator up
                                     check_game_manager!(elevator_3_up, "third floor elevator up");
+                                    // Third floor elevator down (after the Asahi fight)
+                                    check_game_manager!(elevator_3_down, "third floor elevator down");
+                                    // Second floor elevator down
+                                    check_game_manager!(elevator_2_down, "second floor elevator down");
+                                    // First floor elevator down
+                                    check_game_manager!(elevator_1_down, "first floor elevator down");
+                                    // Start of the vertical chase
+                                    check_game_manager!(vertical_chase_start, "vertical chase start");
+
+                                    // Boss health thresholds
+                                    if let (Some(boss), Some(old_percentage)) = (
+                                        boss_fight.running(),
+                                        old_game_manager.map(|gm| gm.boss_percentage),
+                                    ) {
+                                        for health in splits.health.all
+                                            .iter()
+                                            .filter(|h| h.crossed(boss, old_percentage, game_manager.boss_percentage))
+                                        {
+                                            // Karasu Tengu splits again in its duo fight
+                                            let key = health.key(boss_fight.fight);
+                                            if settings.boss_splits
+                                                && !splits.completed.get(&key).copied().unwrap_or(false)
+                                            {
+                                                print_message(&format!("Split for {} at {}% health", boss.name(), health.percent));
+                                                splits.completed.insert(key, true);
+                                                timer::split();
+                                            }
+                                        }
+                                    }
+
+                                    // The chase ends with the next progress the game records, an elevator or a boss
+                                    // fight, a death during the chase only puts the player back
+                                    if chase_running
+                                        && (old_game_manager.is_some_and(|gm| game_manager.elevator_taken(&gm))
+                                            || boss_fight.running().is_some())
+                                    {
+                                        chase_running = false;
+                                        if settings.vertical_chase_complete
+                                            && split_ready(
+                                                "vertical_chase_complete",
+                                                &settings,
+                                                &splits.completed,
+                                                &splits.route,
+                                            )
+                                        {
+                                            print_message("Split for vertical chase complete");
+                                            splits.completed
+                                                .insert("vertical_chase_complete".to_string(), true);
+                                            timer::split();
+                                        }
+                                    }
+                                    if old_game_manager.is_some_and(|gm| !gm.vertical_chase_start)
+                                        && game_manager.vertical_chase_start
+                                    {
+                                        chase_running = true;
+                                    }
 
                                     old_game_manager = Some(game_manager);
                                 }
@@ -290,9 +386,12 @@ async fn main() {
                                         }
                                         // This should be every tick we are in game and playing
                                         (player, _) => {
-                                            timer::set_game_time(Duration::seconds_f32(
-                                                player.time_played,
-                                            ));
+                                            let time = if settings.boss_practice {
+                                                (player.time_played - practice_start).max(0.0)
+                                            } else {
+                                                player.time_played
+                                            };
+                                            timer::set_game_time(Duration::seconds_f32(time));
                                         }
                                     }
 
@@ -314,7 +413,8 @@ async fn main() {
                                 if old_scene_name.as_deref() != Some(&name) {
                                     print_message(&format!("new secene {}", name));
                                     // Start timer for the first time
-                                    if old_scene_name.as_deref() == Some("New Main Menu")
+                                    if !settings.boss_practice
+                                        && old_scene_name.as_deref() == Some("New Main Menu")
                                         && name == "CBF Intro"
                                         && old_player_manager
                                             < Some(BetaPlayerDataManager { time_played: 1.0 })
@@ -349,13 +449,10 @@ async fn main() {
                                                 Some(false)
                                                     if quest_manager.$field
                                                         && settings.$field
-                                                        && !completed_splits
-                                                            .get(stringify!($field))
-                                                            .copied()
-                                                            .unwrap_or(true) =>
+                                                        && split_ready(stringify!($field), &settings, &splits.completed, &splits.route) =>
                                                 {
                                                     print_message(concat!("Split for ", $msg));
-                                                    *completed_splits
+                                                    *splits.completed
                                                         .entry(stringify!($field).to_string())
                                                         .or_insert(true) = true;
                                                     timer::split();
@@ -363,6 +460,22 @@ async fn main() {
                                                 _ => {}
                                             }
                                         };
+                                        (Some($case:tt), $cond_field:ident == $val:expr, $setting:ident, $msg:expr) => {
+                                            match old_quest_manager.map(|am| am.$cond_field) {
+                                                Some($case)
+                                                    if quest_manager.$cond_field == $val
+                                                        && settings.$setting
+                                                        && split_ready(stringify!($setting), &settings, &splits.completed, &splits.route) =>
+                                                {
+                                                    print_message(concat!("Split for ", $msg));
+                                                    *splits.completed
+                                                        .entry(stringify!($setting).to_string())
+                                                        .or_insert(true) = true;
+                                                    timer::split();
+                                                }
+                                                _ => {}
+                                            }
+                                        };
                                     }
 
                                     // SPLITS
@@ -377,12 +490,25 @@ async fn main() {
                                     check_quest!(asahi_eye_of_beast_start, "Asahi Eye of Beast start");
                                     // Asahi Eye of Beast end
                                     check_quest!(asahi_eye_of_beast_end, "Asahi Eye of Beast end");
+                                    // Asahi after the Armapillo (PUA) boss
+                                    check_quest!(asahi_post_armapillo_boss, "Asahi after Armapillo boss");
+
+                                    // Tori's prophecies, told and then fulfilled
+                                    check_quest!(tori_bump_told, "Tori bump prophecy told");
+                                    check_quest!(tori_bump_end, "Tori bump prophecy fulfilled");
+                                    check_quest!(tori_bat_told, "Tori bat prophecy told");
+                                    check_quest!(tori_bat_end, "Tori bat prophecy fulfilled");
+                                    check_quest!(tori_dash_told, "Tori dash prophecy told");
+                                    check_quest!(tori_dash_end, "Tori dash prophecy fulfilled");
 
                                     // Shimeji Armapillos Collection quest start
                                     check_quest!(shimeji_quest_start, "Shimeji Armapillo quest start");
+                                    // Shimeji Armapillos collected for the quest
+                                    check_quest!(Some(0), shimeji_armapillos_collect == 1, one_armapillo, "first Armapillo");
+                                    check_quest!(Some(1), shimeji_armapillos_collect == 2, two_armapillo, "second Armapillo");
+                                    check_quest!(Some(2), shimeji_armapillos_collect == 3, three_armapillo, "third Armapillo");
+                                    check_quest!(Some(3), shimeji_armapillos_collect == 4, four_armapillo, "fourth Armapillo");
                                     // Shimeji Armapillos Collection quest end
-                                    //
-                                    // TODO: add setting for number of armadillos collected 0-4
                                     check_quest!(shimeji_quest_end, "Shimeji Armapillo quest end");
 
                                     // Rozu's Requiem Quest start
@@ -430,6 +556,23 @@ async fn main() {
                                     check_quest!(defeat_kaboto_boss, "Kaboto defeated");
                                     // Credits roll you did it
                                     check_quest!(credits_roll, "done");
+                                    // Credits roll with 100% done, the completion is from the last tick
+                                    if old_quest_manager.is_some_and(|q| !q.credits_roll)
+                                        && quest_manager.credits_roll
+                                        && settings.credits_roll_complete
+                                        && split_ready("credits_roll_complete", &settings, &splits.completed, &splits.route)
+                                    {
+                                        if completion.is_complete() {
+                                            print_message("Split for done with 100%");
+                                            splits.completed.insert("credits_roll_complete".to_string(), true);
+                                            timer::split();
+                                        } else {
+                                            print_message(&format!(
+                                                "Credits without 100%, missing: {}",
+                                                completion.missing().join(", ")
+                                            ));
+                                        }
+                                    }
 
                                     old_quest_manager = Some(quest_manager);
                                 }
@@ -455,13 +598,10 @@ async fn main() {
                                                 Some(false)
                                                     if ability_manager.$field
                                                         && settings.$field
-                                                        && !completed_splits
-                                                            .get(stringify!($field))
-                                                            .copied()
-                                                            .unwrap_or(true) =>
+                                                        && split_ready(stringify!($field), &settings, &splits.completed, &splits.route) =>
                                                 {
                                                     print_message(concat!("Split for ", $msg));
-                                                    *completed_splits
+                                                    *splits.completed
                                                         .entry(stringify!($field).to_string())
                                                         .or_insert(true) = true;
                                                     timer::split();
@@ -514,13 +654,10 @@ async fn main() {
                                                 Some($case)
                                                     if inventory_container.$cond_field == $val
                                                         && settings.$setting
-                                                        && !completed_splits
-                                                            .get(stringify!($setting))
-                                                            .copied()
-                                                            .unwrap_or(true) =>
+                                                        && split_ready(stringify!($setting), &settings, &splits.completed, &splits.route) =>
                                                 {
                                                     print_message(concat!("Split for ", $msg));
-                                                    *completed_splits
+                                                    *splits.completed
                                                         .entry(stringify!($setting).to_string())
                                                         .or_insert(true) = true;
                                                     timer::split();
@@ -539,60 +676,19 @@ async fn main() {
                                     check_inventory!(Some(3), tablets == 4, four_vs_tablet, "fourth VS tablet");
                                     check_inventory!(Some(4), tablets == 5, five_vs_tablet, "fifth VS tablet");
 
-                                    match old_inventory_container.map(|am| am.number_of_kodamas) {
-                                        Some(old_number)
-                                            if old_number < inventory_container.number_of_kodamas
-                                                && settings.number_of_kodamas != NumberOfKodamas::NoSplit =>
-                                        {
-                                            print_message(&format!("KODAMAS: {} {} {:?} {:#?}", old_number, inventory_container.number_of_kodamas, settings.number_of_kodamas, completed_splits));
-
-                                            match settings.number_of_kodamas {
-                                                NumberOfKodamas::EveryOne => {
-                                                    if !completed_splits
-                                                        .get(&format!("number_of_kodamas__{}", inventory_container.number_of_kodamas))
-                                                        .copied()
-                                                        .unwrap_or(false)
-                                                    {
-                                                        print_message(&format!("Split for number of Kodama's {}", inventory_container.number_of_kodamas));
-                                                        *completed_splits
-                                                            .entry(format!("number_of_kodamas__{}", inventory_container.number_of_kodamas))
-                                                            .or_insert(true) = true;
-                                                        timer::split();
-                                                    }
-                                                },
-                                                NumberOfKodamas::EveryFive if inventory_container.number_of_kodamas % 5 == 0 => {
-                                                    if !completed_splits
-                                                        .get(&format!("number_of_kodamas__{}", inventory_container.number_of_kodamas))
-                                                        .copied()
-                                                        .unwrap_or(false)
-                                                    {
-                                                        print_message(&format!("Split for number of Kodama's {}", inventory_container.number_of_kodamas));
-                                                        *completed_splits
-                                                            .entry(format!("number_of_kodamas__{}", inventory_container.number_of_kodamas))
-                                                            .or_insert(true) = true;
-                                                        timer::split();
-                                                    }
-                                                },
-                                                NumberOfKodamas::EveryTen if (inventory_container.number_of_kodamas % 10 == 0)
-                                                    || (inventory_container.number_of_kodamas == 35) =>
-                                                {
-                                                    if !completed_splits
-                                                        .get(&format!("number_of_kodamas__{}", inventory_container.number_of_kodamas))
-                                                        .copied()
-                                                        .unwrap_or(false)
-                                                    {
-                                                        print_message(&format!("Split for number of Kodama's {}", inventory_container.number_of_kodamas));
-                                                        *completed_splits
-                                                            .entry(format!("number_of_kodamas__{}", inventory_container.number_of_kodamas))
-                                                            .or_insert(true) = true;
-                                                        timer::split();
-                                                    }
-                                                },
-                                                _ => {}
-                                            }
-                                        }
-                                        _ => {}
-                                    }
+                                    // 3 music sheets give the grapple
+                                    check_inventory!(Some(0), music_sheets == 1, one_music_sheet, "first music sheet");
+                                    check_inventory!(Some(1), music_sheets == 2, two_music_sheet, "second music sheet");
+                                    check_inventory!(Some(2), music_sheets == 3, three_music_sheet, "third music sheet");
+
+                                    check_inventory!(Some(0), omamori_straps == 1, one_omamori_strap, "first omamori strap");
+                                    check_inventory!(Some(1), omamori_straps == 2, two_omamori_strap, "second omamori strap");
+                                    check_inventory!(Some(2), omamori_straps == 3, three_omamori_strap, "third omamori strap");
+                                    check_inventory!(Some(3), omamori_straps == 4, four_omamori_strap, "fourth omamori strap");
+
+                                    // Key items, handing them in can't be told apart from losing them
+                                    check_inventory!(Some(false), fragile_egg == true, got_fragile_egg, "fragile egg");
+                                    check_inventory!(Some(false), has_kitsune_kifuda == true, got_kitsune_kifuda, "Kitsune Kifuda scroll");
 
                                     old_inventory_container = Some(inventory_container);
                                 }
@@ -638,64 +734,124 @@ async fn main() {
                                         if new_boss != old_boss
                                             && new_boss.boss_kind == old_boss.boss_kind
                                         {
+                                            // A fight is started when the boss becomes in progress without being beaten
+                                            let fight_started = !fight_in_progress(old_boss)
+                                                && fight_in_progress(new_boss)
+                                                && !new_boss.defeated;
+                                            let defeated = !old_boss.defeated && new_boss.defeated;
+
+                                            let time_played = old_player_manager.map_or(0.0, |p| p.time_played);
+                                            if fight_started {
+                                                boss_fight.start(new_boss, time_played);
+                                                run_stats.fight_started(new_boss, time_played);
+                                            } else if boss_fight.is_running(new_boss.boss_kind)
+                                                && (defeated || !fight_in_progress(new_boss))
+                                            {
+                                                boss_fight.end(time_played);
+                                            }
+                                            if defeated {
+                                                run_stats.defeated(new_boss, time_played);
+                                            }
+
+                                            if settings.boss_practice {
+                                                if fight_started {
+                                                    // Every attempt can split on the health of the boss again
+                                                    splits.completed.retain(|k, _| !k.starts_with(BOSS_HEALTH_PREFIX));
+                                                    // Retrying after a death starts a new attempt
+                                                    if timer::state() != TimerState::NotRunning {
+                                                        timer::reset();
+                                                    }
+                                                    print_message(&format!("Practice {:?} fight started", new_boss.boss_kind));
+                                                    practice_start = time_played;
+                                                    timer::start();
+                                                } else if defeated && timer::state() == TimerState::Running {
+                                                    print_message(&format!("Practice {:?} defeated", new_boss.boss_kind));
+                                                    timer::split();
+                                                }
+                                                continue;
+                                            }
+
                                             macro_rules! check_boss {
-                                                ($field:ident, $msg:expr) => {
-                                                    if !old_boss.defeated
-                                                        && new_boss.defeated
+                                                (@split $edge:expr, $field:ident, $msg:expr) => {
+                                                    if $edge
                                                         && settings.$field
-                                                        && !completed_splits
-                                                            .get(stringify!($field))
-                                                            .copied()
-                                                            .unwrap_or(true)
+                                                        && split_ready(stringify!($field), &settings, &splits.completed, &splits.route)
                                                     {
                                                         print_message(concat!("Split for ", $msg));
-                                                        *completed_splits
+                                                        *splits.completed
                                                             .entry(stringify!($field).to_string())
                                                             .or_insert(true) = true;
                                                         timer::split();
                                                     }
                                                 };
+                                                (start $field:ident, $msg:expr) => {
+                                                    check_boss!(@split fight_started, $field, $msg)
+                                                };
+                                                (start $field:ident, $extra:ident == $val:expr, $msg:expr) => {
+                                                    check_boss!(@split fight_started && new_boss.$extra == $val, $field, $msg)
+                                                };
+                                                ($field:ident, $msg:expr) => {
+                                                    check_boss!(@split defeated, $field, $msg)
+                                                };
                                                 ($field:ident, $extra:ident == $val:expr, $msg:expr) => {
-                                                    if !old_boss.defeated
-                                                        && new_boss.defeated
-                                                        && new_boss.$extra == $val
-                                                        && settings.$field
-                                                        && !completed_splits
-                                                            .get(stringify!($field))
-                                                            .copied()
-                                                            .unwrap_or(true)
-                                                    {
-                                                        print_message(concat!("Split for ", $msg));
-                                                        *completed_splits
-                                                            .entry(stringify!($field).to_string())
-                                                            .or_insert(true) = true;
-                                                        timer::split();
-                                                    }
+                                                    check_boss!(@split defeated && new_boss.$extra == $val, $field, $msg)
                                                 };
                                             }
 
                                             match new_boss.boss_kind {
                                                 BossKind::Placeholder => {}
-                                                BossKind::KiriKiriBozu => check_boss!(defeated_kirikiri_boss, "KiriKiri Bozu defeated"),
-                                                BossKind::PUA => check_boss!(defeated_pua_boss, "PUA defeated split"),
-                                                BossKind::Hashihime => check_boss!(defeat_hashihime_boss, "Hashihime defeated"),
-                                                BossKind::Yuki => {
-                                                    // TODO: who dis...
-                                                    print_message(&format!("Yuki boss matched: {:#?}\n{:#?}", new_boss, old_boss));
-                                                },
-                                                BossKind::Yokozuna => check_boss!(defeat_kaboto_boss, "Yokozuna defeated split"),
-                                                BossKind::Jorogumo => check_boss!(defeat_jorogumo_boss, "Jorojumo defeated"),
+                                                BossKind::KiriKiriBozu => {
+                                                    check_boss!(start start_kirikiri_fight, "KiriKiri Bozu fight started");
+                                                    check_boss!(defeat_kirikiri_boss, "KiriKiri Bozu defeated");
+                                                }
+                                                BossKind::PUA => {
+                                                    check_boss!(start start_pua_fight, "PUA fight started");
+                                                    check_boss!(defeat_pua_boss, "PUA defeated split");
+                                                }
+                                                BossKind::Hashihime => {
+                                                    check_boss!(start start_hashihime_fight, "Hashihime fight started");
+                                                    check_boss!(defeat_hashihime_boss, "Hashihime defeated");
+                                                }
+                                                BossKind::Yokozuna => {
+                                                    check_boss!(start start_kaboto_fight, "Yokozuna fight started");
+                                                    check_boss!(defeat_kaboto_boss, "Yokozuna defeated split");
+                                                }
+                                                BossKind::Jorogumo => {
+                                                    check_boss!(start start_jorogumo_fight, "Jorojumo fight started");
+                                                    check_boss!(defeat_jorogumo_boss, "Jorojumo defeated");
+                                                }
                                                 BossKind::KarasuTengu => {
+                                                    check_boss!(start start_karasu_tengu_one_fight, total_health == 133.0, "KarasuTengu single fight started");
+                                                    check_boss!(start start_karasu_tengu_two_fight, total_health == 225.0, "KarasuTengu duo fight started");
                                                     check_boss!(defeat_karasu_tengu_one_boss, total_health == 133.0, "KarasuTengu single defeated");
                                                     check_boss!(defeat_karasu_tengu_two_boss, total_health == 225.0, "KarasuTengu duo defeated");
                                                 }
-                                                BossKind::DaiTengu => check_boss!(defeat_dai_tengu_boss, "DaiTengu (Tengu Trio) defeated"),
-                                                BossKind::Gasha => check_boss!(defeat_gash_boss, "Gashadokuro defeated"),
-                                                BossKind::Asahi => check_boss!(defeat_ashai_boss, "Asahi defeated"),
-                                                BossKind::Shogun => check_boss!(defeat_sakura_boss, "Sakura Shogun defeated"),
-                                                // TODO: who dis...
+                                                BossKind::DaiTengu => {
+                                                    check_boss!(start start_dai_tengu_fight, "DaiTengu (Tengu Trio) fight started");
+                                                    check_boss!(defeat_dai_tengu_boss, "DaiTengu (Tengu Trio) defeated");
+                                                }
+                                                BossKind::Gasha => {
+                                                    check_boss!(start start_gash_fight, "Gashadokuro fight started");
+                                                    check_boss!(defeat_gash_boss, "Gashadokuro defeated");
+                                                }
+                                                BossKind::Asahi => {
+                                                    check_boss!(start start_asahi_fight, "Asahi fight started");
+                                                    check_boss!(defeat_asahi_boss, "Asahi defeated");
+                                                }
+                                                BossKind::Shogun => {
+                                                    check_boss!(start start_sakura_fight, "Sakura Shogun fight started");
+                                                    check_boss!(defeat_sakura_boss, "Sakura Shogun defeated");
+                                                }
+                                                // Where these fights happen isn't known yet, log them to find out
+                                                BossKind::Yuki => {
+                                                    print_message(&format!("Yuki boss matched: {:#?}\n{:#?}", new_boss, old_boss));
+                                                    check_boss!(start start_yuki_fight, "Yuki fight started");
+                                                    check_boss!(defeat_yuki_boss, "Yuki defeated");
+                                                }
                                                 BossKind::Amaterasu => {
                                                     print_message(&format!("Amaterasu boss matched: {:#?}\n{:#?}", new_boss, old_boss));
+                                                    check_boss!(start start_amaterasu_fight, "Amaterasu fight started");
+                                                    check_boss!(defeat_amaterasu_boss, "Amaterasu defeated");
                                                 }
                                             }
                                         }
@@ -728,16 +884,16 @@ async fn main() {
                                         {
                                             macro_rules! check_daruma {
                                                 ($field:ident, $msg:expr) => {
-                                                    if !old_daruma.available
-                                                        && new_daruma.available
+                                                    check_daruma!(available $field, $msg)
+                                                };
+                                                ($flag:ident $field:ident, $msg:expr) => {
+                                                    if !old_daruma.$flag
+                                                        && new_daruma.$flag
                                                         && settings.$field
-                                                        && !completed_splits
-                                                            .get(stringify!($field))
-                                                            .copied()
-                                                            .unwrap_or(true)
+                                                        && split_ready(stringify!($field), &settings, &splits.completed, &splits.route)
                                                     {
                                                         print_message(concat!("Split for ", $msg));
-                                                        *completed_splits
+                                                        *splits.completed
                                                             .entry(stringify!($field).to_string())
                                                             .or_insert(true) = true;
                                                         timer::split();
@@ -746,16 +902,48 @@ async fn main() {
                                             }
 
                                             match new_daruma.daruma_type {
-                                                DarumaType::Bite => check_daruma!(got_chomper_daruma, "Chomper Daruma"),
-                                                DarumaType::Parry => check_daruma!(got_mamori_daruma, "Mamori Daruma"),
-                                                DarumaType::Thorns => check_daruma!(got_togichan_daruma, "TogiChan Daruma"),
-                                                DarumaType::Spirits => check_daruma!(got_jingu_daruma, "Jingu Daruma"),
-                                                DarumaType::Bomb => check_daruma!(got_kaboomaru_daruma, "Kaboomaru Daruma"),
+                                                DarumaType::Bite => {
+                                                    check_daruma!(got_chomper_daruma, "Chomper Daruma");
+                                                    check_daruma!(two_eyes upgraded_chomper_daruma, "Chomper Daruma two eyes");
+                                                    check_daruma!(is_active equipped_chomper_daruma, "Chomper Daruma equipped");
+                                                }
+                                                DarumaType::Parry => {
+                                                    check_daruma!(got_mamori_daruma, "Mamori Daruma");
+                                                    check_daruma!(two_eyes upgraded_mamori_daruma, "Mamori Daruma two eyes");
+                                                    check_daruma!(is_active equipped_mamori_daruma, "Mamori Daruma equipped");
+                                                }
+                                                DarumaType::Thorns => {
+                                                    check_daruma!(got_togichan_daruma, "TogiChan Daruma");
+                                                    check_daruma!(two_eyes upgraded_togichan_daruma, "TogiChan Daruma two eyes");
+                                                    check_daruma!(is_active equipped_togichan_daruma, "TogiChan Daruma equipped");
+                                                }
+                                                DarumaType::Spirits => {
+                                                    check_daruma!(got_jingu_daruma, "Jingu Daruma");
+                                                    check_daruma!(two_eyes upgraded_jingu_daruma, "Jingu Daruma two eyes");
+                                                    check_daruma!(is_active equipped_jingu_daruma, "Jingu Daruma equipped");
+                                                }
+                                                DarumaType::Bomb => {
+                                                    check_daruma!(got_kaboomaru_daruma, "Kaboomaru Daruma");
+                                                    check_daruma!(two_eyes upgraded_kaboomaru_daruma, "Kaboomaru Daruma two eyes");
+                                                    check_daruma!(is_active equipped_kaboomaru_daruma, "Kaboomaru Daruma equipped");
+                                                }
                                                 DarumaType::SpinAttack => {}
                                                 DarumaType::Deprecated1 => {}
-                                                DarumaType::FireWall => check_daruma!(got_pyro_daruma, "PyroKun Daruma"),
-                                                DarumaType::Ice => check_daruma!(got_yuki_daruma, "Yuki Daruma"),
-                                                DarumaType::Boomerang => check_daruma!(got_ken_daruma, "Ken Daruma"),
+                                                DarumaType::FireWall => {
+                                                    check_daruma!(got_pyro_daruma, "PyroKun Daruma");
+                                                    check_daruma!(two_eyes upgraded_pyro_daruma, "PyroKun Daruma two eyes");
+                                                    check_daruma!(is_active equipped_pyro_daruma, "PyroKun Daruma equipped");
+                                                }
+                                                DarumaType::Ice => {
+                                                    check_daruma!(got_yuki_daruma, "Yuki Daruma");
+                                                    check_daruma!(two_eyes upgraded_yuki_daruma, "Yuki Daruma two eyes");
+                                                    check_daruma!(is_active equipped_yuki_daruma, "Yuki Daruma equipped");
+                                                }
+                                                DarumaType::Boomerang => {
+                                                    check_daruma!(got_ken_daruma, "Ken Daruma");
+                                                    check_daruma!(two_eyes upgraded_ken_daruma, "Ken Daruma two eyes");
+                                                    check_daruma!(is_active equipped_ken_daruma, "Ken Daruma equipped");
+                                                }
                                             }
                                         }
                                     }
@@ -764,6 +952,99 @@ async fn main() {
                                 old_daruma_list = new_daruma_list;
                             }
 
+                            // SPLITS for damage upgrades
+                            for kind in DamageKind::ALL {
+                                let damage = match kind {
+                                    DamageKind::Staff => old_enemies_manager.map(|e| e.staff_damage),
+                                    DamageKind::Base => old_inventory_container.map(|i| i.base_damage),
+                                    DamageKind::DarumaBoost => old_daruma_manager.map(|d| d.daruma_boost_damage),
+                                };
+                                let Some(damage) = damage else {
+                                    continue;
+                                };
+                                splits.dashboard.set(kind.variable(), &format!("{:.1}", damage));
+                                if !damage_upgrades.update(kind, damage) {
+                                    continue;
+                                }
+                                print_message(&format!("{:?} damage upgraded to {:.1}", kind, damage));
+
+                                let (first, first_enabled) = match kind {
+                                    DamageKind::Staff => ("first_staff_upgrade", settings.first_staff_upgrade),
+                                    DamageKind::Base => ("first_base_damage_upgrade", settings.first_base_damage_upgrade),
+                                    DamageKind::DarumaBoost => ("first_daruma_boost_upgrade", settings.first_daruma_boost_upgrade),
+                                };
+                                let total_key = format!("damage_upgrades__{}", damage_upgrades.total());
+                                if damage_upgrades.upgrades(kind) == 1
+                                    && first_enabled
+                                    && split_ready(first, &settings, &splits.completed, &splits.route)
+                                {
+                                    print_message(&format!("Split for {}", first));
+                                    splits.completed.insert(first.to_string(), true);
+                                    timer::split();
+                                } else if settings.every_damage_upgrade
+                                    && settings.upgrade_splits
+                                    && counted_split_ready(&total_key, &settings, &splits.completed, &splits.route)
+                                {
+                                    print_message(&format!("Split for damage upgrade {}", damage_upgrades.total()));
+                                    splits.completed.insert(total_key, true);
+                                    timer::split();
+                                }
+                            }
+                            splits.dashboard.set("Damage upgrades", &damage_upgrades.total().to_string());
+
+                            // SPLITS for user defined conditions
+                            let state = GameState {
+                                scene: old_scene_name.as_deref(),
+                                game: old_game_manager.as_ref(),
+                                quest: old_quest_manager.as_ref(),
+                                ability: old_ability_manager.as_ref(),
+                                inventory: old_inventory_container.as_ref(),
+                                enemies: old_enemies_manager.as_ref(),
+                                daruma_manager: old_daruma_manager.as_ref(),
+                                player: old_player_manager.as_ref(),
+                                bosses: old_boss_list.as_deref(),
+                                darumas: old_daruma_list.as_deref(),
+                            };
+                            boss_fight.publish(
+                                &mut splits.dashboard,
+                                old_game_manager.map_or(0.0, |gm| gm.boss_percentage),
+                                old_player_manager.map_or(0.0, |p| p.time_played),
+                            );
+                            splits.dashboard.update_progress(&state);
+                            completion = Completion::new(&state);
+                            splits.dashboard.update_completion(&completion);
+
+                            for custom in splits.custom.iter_mut() {
+                                if custom.rising(&state)
+                                    && split_ready(&custom.key, &settings, &splits.completed, &splits.route)
+                                {
+                                    print_message(&format!(
+                                        "Split for custom condition {}: {}",
+                                        custom.key,
+                                        custom.condition.source()
+                                    ));
+                                    *splits.completed
+                                        .entry(custom.key.clone())
+                                        .or_insert(true) = true;
+                                    timer::split();
+                                }
+                            }
+
+                            for count in splits.counts.all_mut() {
+                                for n in count.crossed(&state) {
+                                    let key = count.key(n);
+                                    if settings.collectible_splits
+                                        && counted_split_ready(&key, &settings, &splits.completed, &splits.route)
+                                    {
+                                        print_message(&format!("Split for {}", count.label(n)));
+                                        splits.completed.insert(key, true);
+                                        timer::split();
+                                    }
+                                }
+                            }
+
+                            splits.dashboard.update_splits(&splits.completed, &splits.route, &splits.split_file.segments);
+
                             next_tick().await;
                         }
                     }
@@ -799,32 +1080,183 @@ async fn get_daruma_data_array(
     Ok(all_darumas)
 }
 
-fn update_settings(
-    settings: &mut Settings,
-    old_lss_file: &mut Option<String>,
-    completed: &mut HashMap<String, bool>,
-) {
+/// The splits of the run and what they are loaded from, everything the settings change.
+#[derive(Default)]
+struct SplitState {
+    split_file: SplitFile,
+    /// Every split by key, true once it has split this run.
+    completed: HashMap<String, bool>,
+    custom: Vec<CustomSplit>,
+    health: HealthSplits,
+    counts: CountSplits,
+    /// The route file that was read last, broken files are not read again.
+    old_route_file: Option<String>,
+    route: Route,
+    dashboard: Dashboard,
+}
+
+fn update_settings(settings: &mut Settings, splits: &mut SplitState) {
+    let SplitState {
+        split_file,
+        completed,
+        custom,
+        health,
+        counts,
+        old_route_file,
+        route,
+        dashboard,
+    } = splits;
     settings.update();
+    counts.update(settings);
+
+    let path_changed = (!settings.lss_file.path.is_empty() && split_file.path.is_none())
+        || (split_file.path.is_some() && split_file.path.as_ref() != Some(&settings.lss_file.path));
+    // Saving the splits in LiveSplit mid run re-reads the file, `completed` is kept so splits
+    // already taken don't happen again
+    if path_changed || split_file.changed_on_disk() {
+        if !path_changed {
+            print_message(&format!(
+                "Splits file '{}' changed, reloading",
+                settings.lss_file.path
+            ));
+        }
+        if let Ok(segments) = read_settings_xml(settings) {
+            split_file.path = Some(settings.lss_file.path.clone());
+            split_file.version = FileVersion::read(&settings.lss_file.path);
+            split_file.segments = segments;
+            split_file.auto_mapped = false;
+            split_file.validated = false;
+            load_custom_splits(custom);
+            load_health_splits(health);
+            load_count_splits(counts);
 
-    if (!settings.lss_file.path.is_empty() && old_lss_file.is_none())
-        || (old_lss_file.is_some() && old_lss_file.as_ref() != Some(&settings.lss_file.path))
-    {
-        if read_settings_xml(settings).is_err() {
+            print_message(&format!("Updated map (read file) {:#?}", Map::load()));
+        } else {
             asr::print_message(&format!(
                 "Error: reading xml settings file '{}'",
                 settings.lss_file.path
             ));
-        } else {
-            *old_lss_file = Some(settings.lss_file.path.clone());
+        }
+    }
 
-            print_message(&format!("Updated map (read file) {:#?}", Map::load()));
+    // After the file is read so the splits it lists are in
+    health.update(settings);
+
+    if settings.auto_map_segments && !split_file.auto_mapped && !split_file.segments.is_empty() {
+        let map = Map::load();
+        for (segment, split) in split_file
+            .segments
+            .iter()
+            .zip(auto_map(&split_file.segments))
+        {
+            match split {
+                Some(split) => {
+                    print_message(&format!("Segment '{}' -> {}", segment.name, split.id));
+                    map.insert(split.id, true);
+                }
+                None => asr::print_message(&format!(
+                    "Segment '{}' does not match any split",
+                    segment.name
+                )),
+            }
+        }
+        map.store();
+        split_file.auto_mapped = true;
+        split_file.validated = false;
+    }
+
+    if !settings.route_file.path.is_empty()
+        && old_route_file.as_ref() != Some(&settings.route_file.path)
+    {
+        match Route::load(&settings.route_file.path) {
+            Ok(mut new_route) => {
+                // A typo would never split, so unknown ids are left out of the route
+                new_route.splits.retain(|split| {
+                    // Counted splits are named by their key, `number_of_kodamas__20`
+                    let counted = split.id.rsplit_once("__").is_some_and(|(name, n)| {
+                        n.parse::<i32>().is_ok()
+                            && (name == "damage_upgrades"
+                                || counts.all_mut().any(|count| count.name == name))
+                    });
+                    let known =
+                        split.condition.is_some() || split_info(&split.id).is_some() || counted;
+                    if !known {
+                        asr::print_message(&format!(
+                            "Error: route split '{}' is not a split, it is skipped",
+                            split.id
+                        ));
+                    }
+                    known
+                });
+                for split in &new_route.splits {
+                    if let Some(after) = split
+                        .after
+                        .as_deref()
+                        .filter(|after| new_route.splits.iter().all(|s| s.id != *after))
+                    {
+                        asr::print_message(&format!(
+                            "Error: route split '{}' comes after '{}' which is not in the route",
+                            split.id, after
+                        ));
+                    }
+                }
+                apply_route(route, &new_route);
+
+                print_message(&format!(
+                    "Loaded route '{}': {:?}",
+                    new_route.name,
+                    new_route
+                        .splits
+                        .iter()
+                        .map(|s| &s.label)
+                        .collect::<Vec<_>>()
+                ));
+                *route = new_route;
+                load_custom_splits(custom);
+                split_file.validated = false;
+            }
+            Err(err) => asr::print_message(&format!(
+                "Error: reading route file '{}': {}",
+                settings.route_file.path, err
+            )),
         }
+        // Don't retry a broken file every tick, it is read again once a different file is picked
+        *old_route_file = Some(settings.route_file.path.clone());
+    }
+
+    if settings.export_settings != ExportSettings::Nothing {
+        match export_settings(settings) {
+            Ok(path) => print_message(&format!("Exported settings to '{}'", path)),
+            Err(err) => asr::print_message(&format!("Error: exporting settings: {}", err)),
+        }
+        // This is an action, not a setting
+        let map = Map::load();
+        map.insert("export_settings", "Nothing");
+        map.store();
+    }
+
+    if settings.generate_splits_file != GenerateSplitsFile::Nothing {
+        match generate_splits_file(settings, custom, counts, route) {
+            Ok(path) => print_message(&format!("Generated splits file '{}'", path)),
+            Err(err) => asr::print_message(&format!("Error: generating splits file: {}", err)),
+        }
+        // This is an action, not a setting
+        let map = Map::load();
+        map.insert("generate_splits_file", "Nothing");
+        map.store();
+    }
+
+    if split_file.path.is_some() && !split_file.validated {
+        let other_splits = other_splits(settings, custom, health, counts);
+        report_split_file(settings, split_file, route, other_splits, dashboard);
+        split_file.validated = true;
     }
 
     let map = Map::load();
     let finished = completed
         .keys()
-        .filter(|name| !name.starts_with("number_of_kodamas__"))
+        // Counted splits like `number_of_kodamas__20` are not settings
+        .filter(|name| !name.contains("__"))
         .count();
     if (finished as u64) != map.len() {
         for k in map.keys() {
@@ -835,7 +1267,322 @@ fn update_settings(
     }
 }
 
-fn read_settings_xml(settings: &Settings) -> Result<(), ()> {
+/// Enables the splits of `new_route` and their sections in the settings map, custom splits store their condition. Other built-in splits keep their setting, the ones that are on are logged. The custom splits of the `old_route` are dropped.
+fn apply_route(old_route: &Route, new_route: &Route) {
+    let old_map = Map::load();
+    // The map has no way to remove a key, so it is copied without the old custom splits
+    let map = Map::new();
+    for (key, value) in old_map.iter() {
+        if old_route
+            .splits
+            .iter()
+            .all(|s| s.condition.is_none() || s.id != key)
+        {
+            map.insert(&key, value);
+        }
+    }
+    for split in SPLITS {
+        if new_route.splits.iter().any(|s| s.id == split.id) {
+            map.insert(split.id, true);
+            map.insert(split.group.setting_id(), true);
+        } else if split_enabled(&map, split) {
+            asr::print_message(&format!(
+                "Warning: split '{}' is on but not in the route",
+                split.id
+            ));
+        }
+    }
+    for split in &new_route.splits {
+        if let Some(condition) = &split.condition {
+            map.insert(&split.id, condition.source());
+        }
+    }
+    map.store();
+}
+
+/// Writes the current settings as an `<AutoSplitterSettings>` block next to the splits file or into it, returning the path written.
+fn export_settings(settings: &Settings) -> Result<String, String> {
+    if settings.lss_file.path.is_empty() {
+        return Err("select a splits file first".to_string());
+    }
+
+    let mut xml = vec![];
+    write_settings_xml(&exported_settings(&Map::load()), &mut xml).map_err(|e| e.to_string())?;
+    let xml = String::from_utf8(xml).map_err(|e| e.to_string())?;
+
+    match settings.export_settings {
+        ExportSettings::Nothing => Ok(String::new()),
+        ExportSettings::Fragment => {
+            let path = Path::new(&settings.lss_file.path).with_extension("autosplitter.xml");
+            fs::write(&path, xml).map_err(|e| e.to_string())?;
+            Ok(path.display().to_string())
+        }
+        ExportSettings::PatchSplitsFile => {
+            patch_lss(&settings.lss_file.path, &xml)?;
+            Ok(settings.lss_file.path.clone())
+        }
+    }
+}
+
+/// Prints the boss fights of the run that ended and appends them to `<lss>.bosses.txt`.
+fn report_run_stats(settings: &Settings, stats: &RunStats) {
+    let summary = stats.summary();
+    asr::print_message(&format!("Boss fights:\n{}", summary));
+    if settings.lss_file.path.is_empty() {
+        return;
+    }
+
+    let path = Path::new(&settings.lss_file.path).with_extension("bosses.txt");
+    let written = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut f| writeln!(f, "{} run\n{}", settings.category.name(), summary));
+    match written {
+        Ok(()) => print_message(&format!("Wrote boss fights to '{}'", path.display())),
+        Err(err) => asr::print_message(&format!(
+            "Error: writing boss fights to '{}': {}",
+            path.display(),
+            err
+        )),
+    }
+}
+
+/// Writes a splits file with a segment per split and matching autosplitter settings next to the selected splits file, returning the path written.
+fn generate_splits_file(
+    settings: &Settings,
+    custom: &[CustomSplit],
+    counts: &CountSplits,
+    route: &Route,
+) -> Result<String, String> {
+    if settings.lss_file.path.is_empty() {
+        return Err("select a splits file first".to_string());
+    }
+
+    // A loaded map is only a copy until it is stored, so presets don't change the settings
+    let map = Map::load();
+    let (category, names) = match settings.generate_splits_file {
+        GenerateSplitsFile::Nothing => return Ok(String::new()),
+        GenerateSplitsFile::EnabledSplits if !route.splits.is_empty() => (
+            settings.category,
+            route.splits.iter().map(|s| s.label.clone()).collect(),
+        ),
+        GenerateSplitsFile::EnabledSplits => {
+            let mut names = SPLITS
+                .iter()
+                .filter(|s| split_enabled(&map, s))
+                .map(|s| s.name.to_string())
+                .collect::<Vec<_>>();
+            names.extend(custom.iter().map(CustomSplit::name));
+            names.extend(counts.labels(settings));
+            (settings.category, names)
+        }
+        GenerateSplitsFile::AnyPercent | GenerateSplitsFile::HundredPercent => {
+            let (category, preset) =
+                if settings.generate_splits_file == GenerateSplitsFile::AnyPercent {
+                    (Category::AnyPercent, ANY_PERCENT)
+                } else {
+                    (Category::HundredPercent, HUNDRED_PERCENT)
+                };
+            for split in SPLITS {
+                map.insert(split.id, preset.contains(&split.id));
+                map.insert(split.group.setting_id(), true);
+            }
+            map.insert("number_of_kodamas", "NoSplit");
+            let names = preset
+                .iter()
+                .map(|id| split_info(id).map_or(id.to_string(), |s| s.name.to_string()))
+                .collect();
+            (category, names)
+        }
+    };
+
+    let path =
+        Path::new(&settings.lss_file.path).with_file_name(format!("Bo - {}.lss", category.name()));
+    if path == Path::new(&settings.lss_file.path) {
+        return Err(format!(
+            "won't overwrite the selected splits file '{}'",
+            path.display()
+        ));
+    }
+
+    let names = names.iter().map(String::as_str).collect::<Vec<_>>();
+    let mut lss = vec![];
+    write_lss(&mut lss, category.name(), &names, &exported_settings(&map))
+        .map_err(|e| e.to_string())?;
+    fs::write(&path, lss).map_err(|e| e.to_string())?;
+    Ok(path.display().to_string())
+}
+
+/// Checks the segments of the splits file against the enabled splits, mismatches are printed and shown in the "Split file check" variable.
+fn report_split_file(
+    settings: &Settings,
+    split_file: &SplitFile,
+    route: &Route,
+    other_splits: Option<usize>,
+    dashboard: &mut Dashboard,
+) {
+    // The map is read directly since the settings don't see this tick's auto mapping or route yet
+    let map = Map::load();
+    let (enabled, ordered) = if route.splits.is_empty() {
+        let enabled = SPLITS
+            .iter()
+            .filter(|s| split_enabled(&map, s))
+            .map(|s| s.id)
+            .collect::<Vec<_>>();
+        (enabled, false)
+    } else {
+        let enabled = route
+            .splits
+            .iter()
+            .filter(|s| s.condition.is_none())
+            .map(|s| s.id.as_str())
+            .collect::<Vec<_>>();
+        (enabled, true)
+    };
+
+    let problems = validate(&split_file.segments, &enabled, ordered, other_splits);
+    if problems.is_empty() {
+        print_message("Split file check: segments match the enabled splits");
+        dashboard.set("Split file check", "OK");
+    } else {
+        for problem in &problems {
+            asr::print_message(&format!("Split file check: {}", problem));
+        }
+        dashboard.set("Split file check", &problems.join("; "));
+    }
+}
+
+/// The number of enabled splits that are not in `SPLITS`, `None` when one of them splits an unknown number of times.
+fn other_splits(
+    settings: &Settings,
+    custom: &[CustomSplit],
+    health: &HealthSplits,
+    counts: &CountSplits,
+) -> Option<usize> {
+    // Every damage upgrade splits as often as the runner upgrades
+    if settings.every_damage_upgrade && settings.upgrade_splits {
+        return None;
+    }
+    let health = if settings.boss_splits {
+        health.count()
+    } else {
+        0
+    };
+    Some(custom.len() + health + counts.labels(settings).len())
+}
+
+/// Returns true if the split and its settings section are enabled in `map`.
+fn split_enabled(map: &Map, split: &SplitInfo) -> bool {
+    let enabled = |id: &str| map.get(id).and_then(|v| v.get_bool());
+    enabled(split.id).unwrap_or(false) && enabled(split.group.setting_id()).unwrap_or(true)
+}
+
+/// Returns true outside of boss practice mode if `key` has not split yet, its settings section is enabled and the split the route says it comes after (if any) has.
+fn split_ready(
+    key: &str,
+    settings: &Settings,
+    completed: &HashMap<String, bool>,
+    route: &Route,
+) -> bool {
+    // Settings that are not in `completed` yet are not loaded
+    completed.get(key).is_some_and(|done| !done) && can_split(key, settings, completed, route)
+}
+
+/// Like [`split_ready`] for counted splits like `damage_upgrades__3`, these are not settings so they are only in `completed` once they split.
+fn counted_split_ready(
+    key: &str,
+    settings: &Settings,
+    completed: &HashMap<String, bool>,
+    route: &Route,
+) -> bool {
+    !completed.get(key).copied().unwrap_or(false) && can_split(key, settings, completed, route)
+}
+
+fn can_split(
+    key: &str,
+    settings: &Settings,
+    completed: &HashMap<String, bool>,
+    route: &Route,
+) -> bool {
+    !settings.boss_practice
+        && split_info(key).map_or(true, |info| settings.group_enabled(info.group))
+        && route
+            .dependency(key)
+            .map_or(true, |after| completed.get(after).copied().unwrap_or(false))
+}
+
+/// Moves settings saved under an old id (see `SETTING_ALIASES`) to the current id, the old ids are dropped so they aren't exported again.
+///
+/// When both ids are in the map the old id's value wins, it can only have been saved by the runner before the rename while the current id may only hold what the settings GUI shows by default.
+fn migrate_settings_map() {
+    let map = Map::load();
+    if !SETTING_ALIASES
+        .iter()
+        .any(|(alias, _)| map.get(alias).is_some())
+    {
+        return;
+    }
+
+    // The map has no way to remove a key, so it is copied without the old ids
+    let migrated = Map::new();
+    for (key, value) in map.iter() {
+        if SETTING_ALIASES.iter().all(|(alias, _)| *alias != key) {
+            migrated.insert(&key, value);
+        }
+    }
+    for (alias, canonical) in SETTING_ALIASES {
+        if let Some(value) = map.get(alias) {
+            if map.get(canonical).is_some() {
+                asr::print_message(&format!(
+                    "Migrated setting '{}' to '{}', replacing the value of '{}'",
+                    alias, canonical, canonical
+                ));
+            } else {
+                asr::print_message(&format!("Migrated setting '{}' to '{}'", alias, canonical));
+            }
+            migrated.insert(canonical, value);
+        }
+    }
+    migrated.store();
+}
+
+/// Parses every `custom_split*` condition stored in the settings map.
+fn load_custom_splits(custom: &mut Vec<CustomSplit>) {
+    let map = Map::load();
+
+    custom.clear();
+    for key in map.keys().filter(|k| k.starts_with(CUSTOM_SPLIT_PREFIX)) {
+        let Some(src) = map.get(&key).and_then(|v| v.get_string()) else {
+            continue;
+        };
+        match Condition::parse(&src) {
+            Ok(condition) => custom.push(CustomSplit::new(key.to_string(), condition)),
+            Err(err) => asr::print_message(&format!(
+                "Error: custom split '{}' condition '{}': {}",
+                key, src, err
+            )),
+        }
+    }
+}
+
+/// Reads the health splits from the `boss_health_splits` setting of the `.lss`, malformed entries are logged and left out.
+fn load_health_splits(health: &mut HealthSplits) {
+    for err in health.load(&Map::load()) {
+        asr::print_message(&format!("Error: boss health split {}", err));
+    }
+}
+
+/// Reads the `kodama_thresholds` and `count_splits` settings of the `.lss`, malformed entries are logged and left out.
+fn load_count_splits(counts: &mut CountSplits) {
+    for err in counts.load(&Map::load()) {
+        asr::print_message(&format!("Error: count split {}", err));
+    }
+}
+
+fn read_settings_xml(settings: &Settings) -> Result<Vec<Segment>, ()> {
+    let mut segments = SegmentReader::default();
+
     match File::open(&settings.lss_file.path) {
         Ok(f) => {
             let map = Map::load();
@@ -843,99 +1590,23 @@ fn read_settings_xml(settings: &Settings) -> Result<(), ()> {
             let file = BufReader::new(f); // Buffering is important for performance
             let parser = EventReader::new(file);
 
-            let mut in_autosplitter = false;
-            let mut in_splits = false;
-            let mut current_name = None;
+            let mut custom_settings = SettingsReader::default();
             for ev_result in parser {
                 match ev_result {
                     Ok(ev) => {
-                        match_xml(
-                            ev,
-                            &map,
-                            &mut current_name,
-                            &mut in_splits,
-                            &mut in_autosplitter,
-                        );
+                        segments.event(&ev);
+                        custom_settings.event(&ev);
                     }
                     Err(err) => asr::print_message(&format!("Error in read: {}", err)),
                 }
             }
+            custom_settings.store_in(&map);
             map.store();
+            // Older splits files can have settings under an old id
+            migrate_settings_map();
         }
         Err(e) => asr::print_message(&format!("Error in read: {}", e)),
     }
 
-    Ok(())
-}
-
-fn match_xml(
-    e: ReaderEvent,
-    map: &Map,
-    current_name: &mut Option<String>,
-    in_splits: &mut bool,
-    in_autosplitter: &mut bool,
-) {
-    match &e {
-        ReaderEvent::StartElement { name, .. } if name.local_name == "CustomSettings" => {
-            *in_autosplitter = true;
-        }
-        ReaderEvent::StartElement {
-            name, attributes, ..
-        } if name.local_name == "Setting" => {
-            print_message(&format!("{:?}", attributes));
-
-            // This is the normal key is the Setting tag's id
-            if let Some(attr) = attributes.first().filter(|attr| {
-                attr.name.local_name == "id" && !attr.value.is_empty() && attr.value != "lss_file"
-            }) {
-                *current_name = Some(attr.value.clone());
-                *in_splits = true;
-            }
-
-            // Here we have a Setting tag where the value is in one of the attributes
-            //
-            // This is for the lss_file setting
-            if let Some(attr) = attributes
-                .first()
-                // The first attribute has the key (we know it to be lss_file)
-                .filter(|attr| attr.name.local_name == "id" && attr.value == "lss_file")
-                .and_then(|_| attributes.last())
-                // Last attribute has the name of the file
-                .filter(|attr| attr.name.local_name == "value" && !attr.value.is_empty())
-            {
-                map.insert("lss_file", attr.value.as_str());
-                // We know this has no Characters() event next so skip it
-                *in_splits = false;
-                *current_name = None;
-            }
-
-            // This is for the `number_of_kodamas` setting
-            if let Some(attr) = attributes
-                .first()
-                // The first attribute has the key (we know it to be lss_file)
-                .filter(|attr| attr.name.local_name == "id" && attr.value == "number_of_kodamas")
-                .and_then(|_| attributes.last())
-                // Last attribute has the name of the file
-                .filter(|attr| attr.name.local_name == "value" && !attr.value.is_empty())
-            {
-                map.insert("number_of_kodamas", attr.value.as_str());
-                // We know this has no Characters() event next so skip it
-                *in_splits = false;
-                *current_name = None;
-            }
-        }
-        ReaderEvent::Characters(val)
-            if *in_autosplitter && *in_splits && current_name.is_some() =>
-        {
-            map.insert(current_name.as_ref().unwrap(), val == "True");
-            *current_name = None;
-        }
-        ReaderEvent::EndElement { name } if name.local_name == "CustomSettings" => {
-            *in_autosplitter = false;
-        }
-        ReaderEvent::EndElement { name } if name.local_name == "Setting" => {
-            *in_splits = false;
-        }
-        _ => {}
-    }
+    Ok(segments.segments)
 }
@@ -0,0 +1,1018 @@
+//! Reading and writing LiveSplit `.lss` files, the autosplitter settings and the segments.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    io::Write,
+    time::SystemTime,
+};
+
+use asr::settings::{List, Map, Value};
+use xml::{
+    attribute::OwnedAttribute,
+    reader::XmlEvent as ReaderEvent,
+    writer::{self, EmitterConfig, EventWriter, XmlEvent as WriterEvent},
+};
+
+use crate::settings::{split_info, SplitInfo, SPLITS};
+
+/// A segment of the splits file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    /// The name without the subsplit `-` prefix or `{Group}` name.
+    pub name: String,
+    /// The `{Group}` of the last subsplit of a group.
+    pub group: Option<String>,
+    /// Subsplits start with a `-`.
+    pub subsplit: bool,
+}
+
+impl Segment {
+    pub fn parse(raw: &str) -> Self {
+        let raw = raw.trim();
+        let (subsplit, rest) = match raw.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, raw),
+        };
+        let (group, name) = match rest.strip_prefix('{').and_then(|r| r.split_once('}')) {
+            Some((group, name)) => (Some(group.trim().to_string()), name),
+            None => (None, rest),
+        };
+        Self {
+            name: name.trim().to_string(),
+            group,
+            subsplit,
+        }
+    }
+}
+
+/// The `type` attribute of a `<Setting>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SettingType {
+    Bool,
+    I64,
+    F64,
+    /// Strings, file paths and choices (the choice is stored by its key).
+    String,
+    List,
+    Map,
+}
+
+impl SettingType {
+    fn parse(ty: Option<&str>, has_value: bool) -> Result<Self, String> {
+        Ok(match ty {
+            Some("bool") => SettingType::Bool,
+            Some("i64") => SettingType::I64,
+            Some("f64") => SettingType::F64,
+            Some("string") => SettingType::String,
+            Some("list") => SettingType::List,
+            Some("map") => SettingType::Map,
+            Some(ty) => return Err(format!("unknown setting type '{}'", ty)),
+            // Older files have no type, the value attribute is only used by strings
+            None if has_value => SettingType::String,
+            None => SettingType::Bool,
+        })
+    }
+}
+
+/// A setting as it is stored in a splits file, converted from and to the values of a settings [`Map`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Setting {
+    Bool(bool),
+    I64(i64),
+    F64(f64),
+    /// Strings, file paths and choices (the choice is stored by its key).
+    String(String),
+    List(Vec<Setting>),
+    Map(Vec<(String, Setting)>),
+}
+
+impl Setting {
+    /// Returns `None` for a value of a type splits files can't hold.
+    pub fn from_value(value: &Value) -> Option<Self> {
+        Some(if let Some(b) = value.get_bool() {
+            Setting::Bool(b)
+        } else if let Some(n) = value.get_i64() {
+            Setting::I64(n)
+        } else if let Some(n) = value.get_f64() {
+            Setting::F64(n)
+        } else if let Some(s) = value.get_string() {
+            Setting::String(s)
+        } else if let Some(list) = value.get_list() {
+            Setting::List(
+                list.iter()
+                    .filter_map(|item| Self::from_value(&item))
+                    .collect(),
+            )
+        } else if let Some(map) = value.get_map() {
+            Setting::Map(
+                map.iter()
+                    .filter_map(|(key, item)| Some((key, Self::from_value(&item)?)))
+                    .collect(),
+            )
+        } else {
+            return None;
+        })
+    }
+
+    /// The entries of a list setting, either a string of entries separated by `sep` or a list with an entry per item. Empty entries are left out.
+    pub fn entries(&self, sep: char) -> Vec<Result<String, String>> {
+        let entries = match self {
+            Setting::String(src) => src.split(sep).map(|entry| Ok(entry.to_string())).collect(),
+            Setting::List(items) => items
+                .iter()
+                .map(|item| match item {
+                    Setting::String(s) => Ok(s.clone()),
+                    Setting::I64(n) => Ok(n.to_string()),
+                    Setting::F64(n) => Ok(n.to_string()),
+                    _ => Err("has an item that isn't text".to_string()),
+                })
+                .collect(),
+            _ => vec![Err("must be a string or list setting".to_string())],
+        };
+        entries
+            .into_iter()
+            .map(|entry| entry.map(|entry| entry.trim().to_string()))
+            .filter(|entry| entry.as_ref().map_or(true, |entry| !entry.is_empty()))
+            .collect()
+    }
+
+    pub fn to_value(&self) -> Value {
+        match self {
+            Setting::Bool(b) => Value::from(*b),
+            Setting::I64(n) => Value::from(*n),
+            Setting::F64(n) => Value::from(*n),
+            Setting::String(s) => Value::from(s.as_str()),
+            Setting::List(items) => {
+                let list = List::new();
+                for item in items {
+                    list.push(item.to_value());
+                }
+                Value::from(&list)
+            }
+            Setting::Map(items) => {
+                let map = Map::new();
+                for (key, item) in items {
+                    map.insert(key, item.to_value());
+                }
+                Value::from(&map)
+            }
+        }
+    }
+}
+
+/// An open `<Setting>` element.
+struct SettingFrame {
+    id: Option<String>,
+    ty: Result<SettingType, String>,
+    value: Option<String>,
+    text: String,
+    /// The children of a list or map setting.
+    list: Vec<Setting>,
+    map: Vec<(String, Setting)>,
+}
+
+fn attribute<'a>(attributes: &'a [OwnedAttribute], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|attr| attr.name.local_name == name)
+        .map(|attr| attr.value.as_str())
+}
+
+/// Reads the `<CustomSettings>` of a splits file.
+///
+/// Attributes are looked up by name and every value is stored with the type the file says it has, nested list and map settings included.
+#[derive(Default)]
+pub struct SettingsReader {
+    in_custom_settings: bool,
+    stack: Vec<SettingFrame>,
+    /// The top level settings in file order.
+    pub settings: Vec<(String, Setting)>,
+}
+
+impl SettingsReader {
+    /// Stores the settings read in `map`.
+    pub fn store_in(&self, map: &Map) {
+        for (id, setting) in &self.settings {
+            map.insert(id, setting.to_value());
+        }
+    }
+
+    pub fn event(&mut self, e: &ReaderEvent) {
+        match e {
+            ReaderEvent::StartElement { name, .. } if name.local_name == "CustomSettings" => {
+                self.in_custom_settings = true;
+            }
+            ReaderEvent::StartElement {
+                name, attributes, ..
+            } if self.in_custom_settings && name.local_name == "Setting" => {
+                let value = attribute(attributes, "value").map(str::to_string);
+                self.stack.push(SettingFrame {
+                    id: attribute(attributes, "id").map(str::to_string),
+                    ty: SettingType::parse(attribute(attributes, "type"), value.is_some()),
+                    value,
+                    text: String::new(),
+                    list: vec![],
+                    map: vec![],
+                });
+            }
+            ReaderEvent::Characters(val) => {
+                if let Some(frame) = self.stack.last_mut() {
+                    frame.text.push_str(val);
+                }
+            }
+            ReaderEvent::EndElement { name } if name.local_name == "Setting" => {
+                let Some(mut frame) = self.stack.pop() else {
+                    return;
+                };
+                let id = frame.id.take();
+                let value = match Self::value(frame) {
+                    Ok(value) => value,
+                    Err(err) => {
+                        asr::print_message(&format!(
+                            "Error: setting '{}': {}",
+                            id.as_deref().unwrap_or_default(),
+                            err
+                        ));
+                        return;
+                    }
+                };
+                match (self.stack.last_mut(), id) {
+                    (Some(parent), _) if parent.ty == Ok(SettingType::List) => {
+                        parent.list.push(value)
+                    }
+                    (Some(parent), Some(id)) => parent.map.push((id, value)),
+                    (None, Some(id)) => self.settings.push((id, value)),
+                    (_, None) => asr::print_message("Error: setting without an id"),
+                }
+            }
+            ReaderEvent::EndElement { name } if name.local_name == "CustomSettings" => {
+                self.in_custom_settings = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn value(frame: SettingFrame) -> Result<Setting, String> {
+        let raw = || frame.value.as_deref().unwrap_or(frame.text.trim());
+        Ok(match frame.ty.as_ref()? {
+            SettingType::Bool => Setting::Bool(frame.text.trim() == "True"),
+            SettingType::I64 => Setting::I64(
+                raw()
+                    .parse::<i64>()
+                    .map_err(|e| format!("'{}' {}", raw(), e))?,
+            ),
+            SettingType::F64 => Setting::F64(
+                raw()
+                    .parse::<f64>()
+                    .map_err(|e| format!("'{}' {}", raw(), e))?,
+            ),
+            SettingType::String => Setting::String(raw().to_string()),
+            SettingType::List => Setting::List(frame.list),
+            SettingType::Map => Setting::Map(frame.map),
+        })
+    }
+}
+
+/// The entries of the list setting `key`, see [`Setting::entries`]. Entries that can't be read are logged and left out.
+pub fn setting_entries(map: &Map, key: &str, sep: char) -> Vec<String> {
+    let Some(setting) = map.get(key).and_then(|value| Setting::from_value(&value)) else {
+        return vec![];
+    };
+    setting
+        .entries(sep)
+        .into_iter()
+        .filter_map(|entry| {
+            entry
+                .map_err(|err| asr::print_message(&format!("Error: '{}' {}", key, err)))
+                .ok()
+        })
+        .collect()
+}
+
+/// Settings that only make sense on this machine, they are not exported.
+const MACHINE_SETTINGS: &[&str] = &[
+    "lss_file",
+    "route_file",
+    "export_settings",
+    "generate_splits_file",
+];
+
+/// The game name LiveSplit uses for Bo.
+const GAME_NAME: &str = "Bo: Path of the Teal Lotus";
+
+/// The settings of `map` to write to a splits file, without the [`MACHINE_SETTINGS`].
+pub fn exported_settings(map: &Map) -> Vec<(String, Setting)> {
+    map.iter()
+        .filter(|(key, _)| !MACHINE_SETTINGS.contains(&key.as_str()))
+        .filter_map(|(key, value)| match Setting::from_value(&value) {
+            Some(setting) => Some((key, setting)),
+            None => {
+                asr::print_message(&format!("Error: can't export setting '{}'", key));
+                None
+            }
+        })
+        .collect()
+}
+
+/// Writes `settings` as a LiveSplit `<AutoSplitterSettings><CustomSettings>` block, the reverse of [`SettingsReader`].
+pub fn write_settings_xml<W: Write>(settings: &[(String, Setting)], out: W) -> writer::Result<()> {
+    let mut writer = EmitterConfig::new()
+        .perform_indent(true)
+        .write_document_declaration(false)
+        .create_writer(out);
+    write_settings(&mut writer, settings)
+}
+
+/// Writes a complete splits file with a segment per name and `settings` as the autosplitter settings.
+pub fn write_lss<W: Write>(
+    out: W,
+    category: &str,
+    segment_names: &[&str],
+    settings: &[(String, Setting)],
+) -> writer::Result<()> {
+    let mut writer = EmitterConfig::new().perform_indent(true).create_writer(out);
+
+    macro_rules! element {
+        ($name:expr) => {
+            writer.write(WriterEvent::start_element($name))?;
+            writer.write(WriterEvent::end_element())?;
+        };
+        ($name:expr, $text:expr) => {
+            writer.write(WriterEvent::start_element($name))?;
+            writer.write(WriterEvent::characters($text))?;
+            writer.write(WriterEvent::end_element())?;
+        };
+    }
+
+    writer.write(WriterEvent::start_element("Run").attr("version", "1.7.0"))?;
+    element!("GameIcon");
+    element!("GameName", GAME_NAME);
+    element!("CategoryName", category);
+    element!("LayoutPath");
+    writer.write(WriterEvent::start_element("Metadata"))?;
+    writer.write(WriterEvent::start_element("Run").attr("id", ""))?;
+    writer.write(WriterEvent::end_element())?;
+    writer.write(WriterEvent::start_element("Platform").attr("usesEmulator", "False"))?;
+    writer.write(WriterEvent::characters("PC"))?;
+    writer.write(WriterEvent::end_element())?;
+    element!("Region");
+    element!("Variables");
+    writer.write(WriterEvent::end_element())?;
+    element!("Offset", "00:00:00");
+    element!("AttemptCount", "0");
+    element!("AttemptHistory");
+
+    writer.write(WriterEvent::start_element("Segments"))?;
+    for name in segment_names {
+        writer.write(WriterEvent::start_element("Segment"))?;
+        element!("Name", name);
+        element!("Icon");
+        writer.write(WriterEvent::start_element("SplitTimes"))?;
+        writer.write(WriterEvent::start_element("SplitTime").attr("name", "Personal Best"))?;
+        writer.write(WriterEvent::end_element())?;
+        writer.write(WriterEvent::end_element())?;
+        element!("BestSegmentTime");
+        element!("SegmentHistory");
+        writer.write(WriterEvent::end_element())?;
+    }
+    writer.write(WriterEvent::end_element())?;
+
+    write_settings(&mut writer, settings)?;
+    writer.write(WriterEvent::end_element())
+}
+
+fn write_settings<W: Write>(
+    writer: &mut EventWriter<W>,
+    settings: &[(String, Setting)],
+) -> writer::Result<()> {
+    writer.write(WriterEvent::start_element("AutoSplitterSettings"))?;
+    writer.write(WriterEvent::start_element("CustomSettings"))?;
+    for (key, setting) in settings {
+        write_setting(writer, Some(key), setting)?;
+    }
+    writer.write(WriterEvent::end_element())?;
+    writer.write(WriterEvent::end_element())
+}
+
+fn write_setting<W: Write>(
+    writer: &mut EventWriter<W>,
+    id: Option<&str>,
+    setting: &Setting,
+) -> writer::Result<()> {
+    let start = WriterEvent::start_element("Setting");
+    // List items have no id
+    let start = match id {
+        Some(id) => start.attr("id", id),
+        None => start,
+    };
+
+    match setting {
+        Setting::Bool(b) => {
+            writer.write(start.attr("type", "bool"))?;
+            writer.write(WriterEvent::characters(if *b { "True" } else { "False" }))?;
+        }
+        Setting::I64(n) => {
+            let n = n.to_string();
+            writer.write(start.attr("type", "i64").attr("value", &n))?;
+        }
+        Setting::F64(n) => {
+            let n = n.to_string();
+            writer.write(start.attr("type", "f64").attr("value", &n))?;
+        }
+        Setting::String(s) => writer.write(start.attr("type", "string").attr("value", s))?,
+        Setting::List(items) => {
+            writer.write(start.attr("type", "list"))?;
+            for item in items {
+                write_setting(writer, None, item)?;
+            }
+        }
+        Setting::Map(items) => {
+            writer.write(start.attr("type", "map"))?;
+            for (key, item) in items {
+                write_setting(writer, Some(key), item)?;
+            }
+        }
+    }
+    writer.write(WriterEvent::end_element())
+}
+
+/// The byte range of the first `<name>` element in `xml`, including a self-closing `<name />`.
+fn find_element(xml: &str, name: &str) -> Result<Option<(usize, usize)>, String> {
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+    let Some(start) = xml.match_indices(&open).map(|(i, _)| i).find(|i| {
+        // Not a longer name like `<CustomSettingsX`
+        matches!(
+            xml[i + open.len()..].chars().next(),
+            Some('>' | '/' | ' ' | '\t' | '\r' | '\n')
+        )
+    }) else {
+        return Ok(None);
+    };
+    let tag_end = xml[start..]
+        .find('>')
+        .map(|end| start + end + 1)
+        .ok_or_else(|| format!("unclosed <{}>", name))?;
+    if xml[..tag_end].ends_with("/>") {
+        return Ok(Some((start, tag_end)));
+    }
+    let end = xml[tag_end..]
+        .find(&close)
+        .map(|end| tag_end + end + close.len())
+        .ok_or_else(|| format!("unclosed <{}>", name))?;
+    Ok(Some((start, end)))
+}
+
+/// Replaces the `<CustomSettings>` of the splits file at `path` with those of `settings`, an `<AutoSplitterSettings>` block as written by [`write_settings_xml`].
+///
+/// LiveSplit keeps the auto splitter's `<Version>` and `<ScriptPath>` next to the custom settings, those are left as they are. The whole block is only added when the splits file has no auto splitter settings yet.
+pub fn patch_lss(path: &str, settings: &str) -> Result<(), String> {
+    let lss = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let patched = patch_settings(&lss, settings)?;
+    fs::write(path, patched).map_err(|e| e.to_string())
+}
+
+/// The splits file `lss` with the settings of [`patch_lss`].
+fn patch_settings(lss: &str, settings: &str) -> Result<String, String> {
+    const AUTO_SPLITTER: &str = "AutoSplitterSettings";
+    const CUSTOM: &str = "CustomSettings";
+
+    let (start, end) = find_element(settings, CUSTOM)?.ok_or("no <CustomSettings> to write")?;
+    let custom = &settings[start..end];
+
+    Ok(match find_element(lss, AUTO_SPLITTER)? {
+        // `<AutoSplitterSettings />`
+        Some((start, end)) if lss[..end].ends_with("/>") => {
+            format!("{}{}{}", &lss[..start], settings, &lss[end..])
+        }
+        Some((start, end)) => {
+            let block = &lss[start..end];
+            match find_element(block, CUSTOM)? {
+                Some((c_start, c_end)) => format!(
+                    "{}{}{}",
+                    &lss[..start + c_start],
+                    custom,
+                    &lss[start + c_end..]
+                ),
+                None => {
+                    let insert =
+                        start + block.rfind("</").ok_or("unclosed <AutoSplitterSettings>")?;
+                    format!("{}{}\n{}", &lss[..insert], custom, &lss[insert..])
+                }
+            }
+        }
+        None => {
+            let end = lss.rfind("</Run>").ok_or("not a splits file, no </Run>")?;
+            format!("{}{}\n{}", &lss[..end], settings, &lss[end..])
+        }
+    })
+}
+
+/// How many ticks between checking the splits file for changes, about a second.
+const CHANGE_CHECK_TICKS: u32 = 120;
+
+/// Identifies a version of a file on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileVersion {
+    Modified(SystemTime),
+    /// Used when the modification time is not available.
+    Hash(u64),
+}
+
+impl FileVersion {
+    pub fn read(path: &str) -> Option<Self> {
+        if let Ok(modified) = fs::metadata(path).and_then(|m| m.modified()) {
+            return Some(FileVersion::Modified(modified));
+        }
+        let bytes = fs::read(path).ok()?;
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        Some(FileVersion::Hash(hasher.finish()))
+    }
+}
+
+/// What was loaded from the selected splits file.
+#[derive(Debug, Default)]
+pub struct SplitFile {
+    /// The path that was read last.
+    pub path: Option<String>,
+    /// The version of the file that was read last.
+    pub version: Option<FileVersion>,
+    ticks_since_check: u32,
+    pub segments: Vec<Segment>,
+    /// The segments have been mapped to splits.
+    pub auto_mapped: bool,
+    /// The segments have been checked against the enabled splits.
+    pub validated: bool,
+}
+
+impl SplitFile {
+    /// Returns true if the file was saved since it was read, this only looks at the file every [`CHANGE_CHECK_TICKS`] calls.
+    pub fn changed_on_disk(&mut self) -> bool {
+        let Some(path) = &self.path else {
+            return false;
+        };
+        self.ticks_since_check += 1;
+        if self.ticks_since_check < CHANGE_CHECK_TICKS {
+            return false;
+        }
+        self.ticks_since_check = 0;
+
+        let version = FileVersion::read(path);
+        version.is_some() && version != self.version
+    }
+}
+
+/// Collects the `<Run><Segments><Segment><Name>` of a splits file.
+#[derive(Debug, Default)]
+pub struct SegmentReader {
+    in_segments: bool,
+    in_name: bool,
+    name: String,
+    pub segments: Vec<Segment>,
+}
+
+impl SegmentReader {
+    pub fn event(&mut self, e: &ReaderEvent) {
+        match e {
+            ReaderEvent::StartElement { name, .. } if name.local_name == "Segments" => {
+                self.in_segments = true;
+            }
+            ReaderEvent::StartElement { name, .. }
+                if self.in_segments && name.local_name == "Name" =>
+            {
+                self.in_name = true;
+                self.name.clear();
+            }
+            ReaderEvent::Characters(val) if self.in_name => self.name.push_str(val),
+            // An empty name has no Characters event so the segment is pushed here
+            ReaderEvent::EndElement { name } if self.in_name && name.local_name == "Name" => {
+                self.in_name = false;
+                self.segments.push(Segment::parse(&self.name));
+            }
+            ReaderEvent::EndElement { name } if name.local_name == "Segments" => {
+                self.in_segments = false;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Lowercase words of `s`, apostrophes are dropped so "Rozu's" is "rozus".
+fn words(s: &str) -> Vec<String> {
+    s.to_lowercase()
+        .replace(['\'', '’'], "")
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            cur[j + 1] = (prev[j] + usize::from(ca != *cb))
+                .min(prev[j + 1] + 1)
+                .min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+/// Plurals and a typo in longer words still match.
+fn word_matches(word: &str, keyword: &str) -> bool {
+    word == keyword
+        || word.strip_suffix('s') == Some(keyword)
+        || (keyword.len() >= 5 && levenshtein(word, keyword) <= 1)
+}
+
+/// How well `segment` matches `split`, the number of words of the best matching phrase.
+pub fn match_score(segment: &str, split: &SplitInfo) -> usize {
+    let seg_words = words(segment);
+    split
+        .keywords
+        .iter()
+        .map(|phrase| words(phrase))
+        .filter(|phrase| {
+            phrase
+                .iter()
+                .all(|kw| seg_words.iter().any(|w| word_matches(w, kw)))
+        })
+        .map(|phrase| phrase.len())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Maps every segment to the best matching split that hasn't been used by an earlier segment.
+///
+/// Segments that match nothing are `None`.
+pub fn auto_map(segments: &[Segment]) -> Vec<Option<&'static SplitInfo>> {
+    let mut used = vec![false; SPLITS.len()];
+    segments
+        .iter()
+        .map(|seg| {
+            // Ties go to the first split in settings order, so repeated names ("Elevator") map
+            // to the next split of that kind
+            let (idx, _) = SPLITS
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !used[*i])
+                .map(|(i, split)| (i, match_score(&seg.name, split)))
+                .filter(|(_, score)| *score > 0)
+                .fold(
+                    None,
+                    |best: Option<(usize, usize)>, (i, score)| match best {
+                        Some((_, best_score)) if best_score >= score => best,
+                        _ => Some((i, score)),
+                    },
+                )?;
+            used[idx] = true;
+            Some(&SPLITS[idx])
+        })
+        .collect()
+}
+
+/// Compares the segments with the enabled splits, returning a message for every mismatch.
+///
+/// `enabled` are the enabled split ids, when `ordered` is true they are in the order they happen (a route) and are checked against the segment order. `other_splits` is the number of enabled splits that are not in `SPLITS` (custom, boss health and count splits), `None` when a split that repeats an unknown number of times is enabled.
+pub fn validate(
+    segments: &[Segment],
+    enabled: &[&str],
+    ordered: bool,
+    other_splits: Option<usize>,
+) -> Vec<String> {
+    let mut problems = vec![];
+
+    if let Some(other_splits) = other_splits {
+        let total = enabled.len() + other_splits;
+        if segments.len() != total {
+            problems.push(format!(
+                "{} segments but {} splits enabled",
+                segments.len(),
+                total
+            ));
+        }
+    }
+
+    let mapped = auto_map(segments);
+    let mut last_segment = None;
+    for id in enabled {
+        let Some(info) = split_info(id) else {
+            continue;
+        };
+        let segment = mapped
+            .iter()
+            .position(|split| split.is_some_and(|s| s.id == info.id))
+            .or_else(|| {
+                segments
+                    .iter()
+                    .position(|seg| match_score(&seg.name, info) > 0)
+            });
+        match segment {
+            Some(idx) => {
+                if ordered && last_segment.is_some_and(|last| idx < last) {
+                    problems.push(format!(
+                        "split `{}` comes before the previous split in the segments ('{}')",
+                        id, segments[idx].name
+                    ));
+                }
+                last_segment = Some(idx);
+            }
+            None => problems.push(format!(
+                "split `{}` enabled but no segment mentions {}",
+                id, info.name
+            )),
+        }
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use xml::EventReader;
+
+    use super::*;
+
+    fn settings() -> Vec<(String, Setting)> {
+        vec![
+            ("defeat_kirikiri_boss".to_string(), Setting::Bool(true)),
+            ("credits_roll".to_string(), Setting::Bool(false)),
+            (
+                "boss_health_splits".to_string(),
+                Setting::String("Sometimes & \"always\" <50%".to_string()),
+            ),
+            ("count".to_string(), Setting::I64(-3)),
+            ("ratio".to_string(), Setting::F64(0.25)),
+            (
+                "custom_splits".to_string(),
+                Setting::List(vec![
+                    Setting::String("boss(225) <= 50".to_string()),
+                    Setting::List(vec![Setting::I64(1), Setting::Bool(true)]),
+                    Setting::Map(vec![(
+                        "name".to_string(),
+                        Setting::String("Kodama".to_string()),
+                    )]),
+                ]),
+            ),
+            (
+                "counts".to_string(),
+                Setting::Map(vec![
+                    ("empty".to_string(), Setting::List(vec![])),
+                    (
+                        "nested".to_string(),
+                        Setting::Map(vec![("on".to_string(), Setting::Bool(true))]),
+                    ),
+                ]),
+            ),
+        ]
+    }
+
+    fn read(xml: &str) -> (Vec<(String, Setting)>, Vec<Segment>) {
+        let mut custom_settings = SettingsReader::default();
+        let mut segments = SegmentReader::default();
+        for ev in EventReader::new(xml.as_bytes()) {
+            let ev = ev.unwrap();
+            custom_settings.event(&ev);
+            segments.event(&ev);
+        }
+        (custom_settings.settings, segments.segments)
+    }
+
+    fn settings_xml(settings: &[(String, Setting)]) -> String {
+        let mut xml = vec![];
+        write_settings_xml(settings, &mut xml).unwrap();
+        String::from_utf8(xml).unwrap()
+    }
+
+    #[test]
+    fn settings_round_trip() {
+        let xml = settings_xml(&settings());
+        assert!(xml.starts_with("<AutoSplitterSettings>"));
+        assert_eq!(read(&xml).0, settings());
+    }
+
+    #[test]
+    fn splits_file_round_trip() {
+        let mut lss = vec![];
+        write_lss(
+            &mut lss,
+            "Any%",
+            &["KiriKiri Bozu", "-{Palace} Elevator", ""],
+            &settings(),
+        )
+        .unwrap();
+        let (read_settings, segments) = read(&String::from_utf8(lss).unwrap());
+        assert_eq!(read_settings, settings());
+        assert_eq!(
+            segments,
+            vec![
+                Segment::parse("KiriKiri Bozu"),
+                Segment::parse("-{Palace} Elevator"),
+                Segment::parse(""),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_livesplit_settings() {
+        let xml = r#"<Run>
+  <AutoSplitterSettings>
+    <Version>1.0</Version>
+    <Setting id="outside">True</Setting>
+    <CustomSettings>
+      <Setting id="defeat_kirikiri_boss" type="bool">True</Setting>
+      <Setting id="count" type="i64" value="7" />
+      <Setting id="ratio" type="f64">0.5</Setting>
+      <Setting id="name" type="string">  Any%  </Setting>
+      <Setting id="broken" type="i64" value="seven" />
+      <Setting id="unknown" type="colour" value="red" />
+      <Setting id="list" type="list">
+        <Setting type="string" value="a" />
+        <Setting type="bool">False</Setting>
+      </Setting>
+    </CustomSettings>
+  </AutoSplitterSettings>
+</Run>"#;
+        assert_eq!(
+            read(xml).0,
+            vec![
+                ("defeat_kirikiri_boss".to_string(), Setting::Bool(true)),
+                ("count".to_string(), Setting::I64(7)),
+                ("ratio".to_string(), Setting::F64(0.5)),
+                ("name".to_string(), Setting::String("Any%".to_string())),
+                (
+                    "list".to_string(),
+                    Setting::List(vec![Setting::String("a".to_string()), Setting::Bool(false)]),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_elements() {
+        let xml = "<Run><CustomSettingsX /><CustomSettings><Setting><Setting /></Setting></CustomSettings></Run>";
+        let (start, end) = find_element(xml, "CustomSettings").unwrap().unwrap();
+        assert_eq!(
+            &xml[start..end],
+            "<CustomSettings><Setting><Setting /></Setting></CustomSettings>"
+        );
+        let (start, end) = find_element(xml, "CustomSettingsX").unwrap().unwrap();
+        assert_eq!(&xml[start..end], "<CustomSettingsX />");
+        assert_eq!(find_element(xml, "Custom").unwrap(), None);
+        assert_eq!(
+            find_element("<Run><Segments>", "Segments"),
+            Err("unclosed <Segments>".to_string())
+        );
+        assert_eq!(
+            find_element("<Run><Segments version=\"1\"", "Segments"),
+            Err("unclosed <Segments>".to_string())
+        );
+    }
+
+    const NEW_SETTINGS: &str = "<AutoSplitterSettings><CustomSettings><Setting id=\"new\" type=\"bool\">True</Setting></CustomSettings></AutoSplitterSettings>";
+
+    #[test]
+    fn patches_custom_settings() {
+        let lss = r#"<Run>
+  <Segments />
+  <AutoSplitterSettings>
+    <Version>1.0</Version>
+    <ScriptPath>bo.wasm</ScriptPath>
+    <CustomSettings>
+      <Setting id="old" type="list">
+        <Setting type="bool">True</Setting>
+      </Setting>
+    </CustomSettings>
+  </AutoSplitterSettings>
+</Run>"#;
+        assert_eq!(
+            patch_settings(lss, NEW_SETTINGS).unwrap(),
+            r#"<Run>
+  <Segments />
+  <AutoSplitterSettings>
+    <Version>1.0</Version>
+    <ScriptPath>bo.wasm</ScriptPath>
+    <CustomSettings><Setting id="new" type="bool">True</Setting></CustomSettings>
+  </AutoSplitterSettings>
+</Run>"#
+        );
+    }
+
+    #[test]
+    fn patches_self_closing_settings() {
+        assert_eq!(
+            patch_settings("<Run><AutoSplitterSettings /></Run>", NEW_SETTINGS).unwrap(),
+            format!("<Run>{}</Run>", NEW_SETTINGS)
+        );
+    }
+
+    #[test]
+    fn adds_missing_settings() {
+        assert_eq!(
+            patch_settings(
+                "<Run><AutoSplitterSettings><Version>1.0</Version></AutoSplitterSettings></Run>",
+                NEW_SETTINGS
+            )
+            .unwrap(),
+            "<Run><AutoSplitterSettings><Version>1.0</Version><CustomSettings><Setting id=\"new\" type=\"bool\">True</Setting></CustomSettings>\n</AutoSplitterSettings></Run>"
+        );
+        assert_eq!(
+            patch_settings("<Run><Segments /></Run>", NEW_SETTINGS).unwrap(),
+            format!("<Run><Segments />{}\n</Run>", NEW_SETTINGS)
+        );
+        assert!(patch_settings("<Layout />", NEW_SETTINGS).is_err());
+        assert!(patch_settings("<Run></Run>", "<AutoSplitterSettings />").is_err());
+    }
+
+    #[test]
+    fn parses_segments() {
+        assert_eq!(
+            Segment::parse(" -{Palace} Elevator "),
+            Segment {
+                name: "Elevator".to_string(),
+                group: Some("Palace".to_string()),
+                subsplit: true,
+            }
+        );
+        assert_eq!(
+            Segment::parse("-Elevator"),
+            Segment {
+                name: "Elevator".to_string(),
+                group: None,
+                subsplit: true,
+            }
+        );
+        assert_eq!(
+            Segment::parse("{Unclosed Credits"),
+            Segment {
+                name: "{Unclosed Credits".to_string(),
+                group: None,
+                subsplit: false,
+            }
+        );
+    }
+
+    #[test]
+    fn matches_words() {
+        assert_eq!(
+            words("Rozu's Requiem (End)"),
+            vec!["rozus", "requiem", "end"]
+        );
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "bat"), 3);
+        assert_eq!(levenshtein("bat", "bat"), 0);
+        assert!(word_matches("armapillos", "armapillo"));
+        assert!(word_matches("armapilo", "armapillo"));
+        assert!(!word_matches("bar", "bat"));
+    }
+
+    #[test]
+    fn scores_the_longest_phrase() {
+        let bat_end = split_info("tori_bat_end").unwrap();
+        assert_eq!(match_score("Bat Prophecy Fulfilled", bat_end), 2);
+        assert_eq!(match_score("Bat", bat_end), 0);
+        let bat_told = split_info("tori_bat_told").unwrap();
+        assert_eq!(match_score("Tori's Bat", bat_told), 2);
+    }
+
+    #[test]
+    fn maps_segments_in_settings_order() {
+        let segments =
+            ["Elevator", "Elevator", "Vertical Chase", "Nothing at all"].map(Segment::parse);
+        let ids = auto_map(&segments)
+            .iter()
+            .map(|split| split.map(|s| s.id))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            vec![
+                Some("elevator_e_up"),
+                Some("elevator_1_up"),
+                Some("vertical_chase_start"),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn validates_segments() {
+        let segments = ["Tori Bat", "Bat Prophecy Fulfilled"].map(Segment::parse);
+        assert!(validate(&segments, &["tori_bat_told", "tori_bat_end"], true, Some(0)).is_empty());
+        assert!(validate(&segments, &["tori_bat_end", "tori_bat_told"], false, None).is_empty());
+        assert_eq!(
+            validate(&segments, &["tori_bat_end", "tori_bat_told", "credits_roll", "no_such_split"], true, Some(1)),
+            vec![
+                "2 segments but 5 splits enabled".to_string(),
+                "split `tori_bat_told` comes before the previous split in the segments ('Tori Bat')".to_string(),
+                "split `credits_roll` enabled but no segment mentions Credits".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,584 @@
+//! Route files, an ordered list of splits shared as a plain TOML or JSON file.
+//!
+//! Only the subset of TOML a route needs is understood, top level `key = "value"` pairs and `[[split]]` tables of `key = "value"` pairs:
+//!
+//! ```toml
+//! name = "Any%"
+//!
+//! [[split]]
+//! id = "defeat_kirikiri_boss"
+//! label = "KiriKiri Bozu"
+//!
+//! [[split]]
+//! id = "kodama_rush"
+//! condition = "inventory.number_of_kodamas >= 20"
+//! after = "defeat_kirikiri_boss"
+//! label = "20 Kodamas"
+//! ```
+//!
+//! A file starting with `{` is read as JSON with the same keys, the splits are a `split` array of objects with string values:
+//!
+//! ```json
+//! {
+//!     "name": "Any%",
+//!     "split": [
+//!         { "id": "defeat_kirikiri_boss", "label": "KiriKiri Bozu" },
+//!         { "id": "kodama_rush", "condition": "inventory.number_of_kodamas >= 20" }
+//!     ]
+//! }
+//! ```
+
+use std::fs;
+
+use crate::{
+    condition::{Condition, CUSTOM_SPLIT_PREFIX},
+    settings::{canonical_id, split_info},
+};
+
+/// One split of a route.
+#[derive(Debug, Clone)]
+pub struct RouteSplit {
+    /// A built-in split id (a `Settings` field) or the key of a custom split.
+    pub id: String,
+    /// When present this split is a custom split with this condition.
+    pub condition: Option<Condition>,
+    /// The id of a split that must be completed before this one can split, the order of the route is not enforced otherwise.
+    pub after: Option<String>,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Route {
+    pub name: String,
+    pub splits: Vec<RouteSplit>,
+}
+
+impl Route {
+    /// Reads and parses the route file at `path`.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let src = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::parse(&src)
+    }
+
+    /// Parses a route, as JSON when it starts with `{` and as TOML otherwise.
+    pub fn parse(src: &str) -> Result<Self, String> {
+        let (name, tables) = if src.trim_start().starts_with('{') {
+            parse_json(src)?
+        } else {
+            parse_toml(src)?
+        };
+        let mut route = Route {
+            name,
+            splits: vec![],
+        };
+
+        for (idx, table) in tables.into_iter().enumerate() {
+            let get = |name: &str| {
+                table
+                    .iter()
+                    .find(|(k, _)| k == name)
+                    .map(|(_, v)| v.clone())
+            };
+            let condition = get("condition")
+                .map(|src| Condition::parse(&src))
+                .transpose()
+                .map_err(|e| format!("split {}: {}", idx + 1, e))?;
+            let id = match (get("id"), &condition) {
+                // Custom split keys always carry the prefix so they can't shadow a built-in split
+                (Some(id), Some(_)) if !id.starts_with(CUSTOM_SPLIT_PREFIX) => {
+                    format!("{}_{}", CUSTOM_SPLIT_PREFIX, id)
+                }
+                (Some(id), _) => canonical_id(&id).to_string(),
+                (None, Some(_)) => format!("{}_route_{}", CUSTOM_SPLIT_PREFIX, idx + 1),
+                (None, None) => {
+                    return Err(format!("split {}: needs an `id` or a `condition`", idx + 1))
+                }
+            };
+            route.splits.push(RouteSplit {
+                label: get("label")
+                    .or_else(|| split_info(&id).map(|s| s.name.to_string()))
+                    .unwrap_or_else(|| id.clone()),
+                after: get("after").map(|after| canonical_id(&after).to_string()),
+                id,
+                condition,
+            });
+        }
+
+        // `after` can name a custom split by the id written in the file
+        let custom_ids = route
+            .splits
+            .iter()
+            .filter(|s| s.condition.is_some())
+            .map(|s| s.id.clone())
+            .collect::<Vec<_>>();
+        for split in &mut route.splits {
+            if let Some(after) = &mut split.after {
+                let prefixed = format!("{}_{}", CUSTOM_SPLIT_PREFIX, after);
+                if custom_ids.contains(&prefixed) {
+                    *after = prefixed;
+                }
+            }
+        }
+
+        Ok(route)
+    }
+
+    /// The split that has to be completed before `id` can split.
+    pub fn dependency(&self, id: &str) -> Option<&str> {
+        self.splits
+            .iter()
+            .find(|s| s.id == id)
+            .and_then(|s| s.after.as_deref())
+    }
+}
+
+/// The `key = "value"` pairs of one split.
+type Table = Vec<(String, String)>;
+
+/// Reads the route name and the `[[split]]` tables.
+fn parse_toml(src: &str) -> Result<(String, Vec<Table>), String> {
+    let mut name = String::new();
+    let mut tables: Vec<Table> = vec![];
+    for (line_no, line) in src.lines().enumerate() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line == "[[split]]" {
+            tables.push(vec![]);
+            continue;
+        }
+        let (key, val) = parse_pair(line).map_err(|e| format!("line {}: {}", line_no + 1, e))?;
+        match tables.last_mut() {
+            Some(table) => table.push((key, val)),
+            None if key == "name" => name = val,
+            None => return Err(format!("line {}: unknown key '{}'", line_no + 1, key)),
+        }
+    }
+    Ok((name, tables))
+}
+
+/// Removes a `#` comment that is not inside a string.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match (c, quote) {
+            // Only basic strings have escapes, `\"` doesn't end them
+            _ if escaped => escaped = false,
+            ('\\', Some('"')) => escaped = true,
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('#', None) => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn parse_pair(line: &str) -> Result<(String, String), String> {
+    let (key, val) = line
+        .split_once('=')
+        .ok_or_else(|| format!("expected `key = \"value\"` found '{}'", line))?;
+    let key = key.trim();
+    let val = val.trim();
+
+    let val = if let Some(lit) = val.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        // Literal strings have no escapes
+        lit.to_string()
+    } else if let Some(basic) = val.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        let mut out = String::with_capacity(basic.len());
+        let mut chars = basic.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some(c @ ('"' | '\\')) => out.push(c),
+                    c => return Err(format!("unknown escape '\\{:?}' in '{}'", c, key)),
+                },
+                c => out.push(c),
+            }
+        }
+        out
+    } else {
+        return Err(format!("the value of '{}' must be a string", key));
+    };
+
+    Ok((key.to_string(), val))
+}
+
+/// A JSON value, a route only has strings in objects and arrays.
+#[derive(Debug, Clone, PartialEq)]
+enum Json {
+    Str(String),
+    Arr(Vec<Json>),
+    Obj(Vec<(String, Json)>),
+}
+
+/// Reads the route name and the `split` objects of a JSON route.
+fn parse_json(src: &str) -> Result<(String, Vec<Table>), String> {
+    let mut parser = JsonParser {
+        chars: src.chars().collect(),
+        pos: 0,
+    };
+    let root = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos < parser.chars.len() {
+        return Err(format!(
+            "unexpected '{}' at {}",
+            parser.chars[parser.pos], parser.pos
+        ));
+    }
+
+    let Json::Obj(fields) = root else {
+        return Err("a route must be an object".to_string());
+    };
+    let mut name = String::new();
+    let mut tables = vec![];
+    for (key, val) in fields {
+        match (key.as_str(), val) {
+            ("name", Json::Str(val)) => name = val,
+            ("split", Json::Arr(splits)) => {
+                for (idx, split) in splits.into_iter().enumerate() {
+                    let Json::Obj(split) = split else {
+                        return Err(format!("split {}: must be an object", idx + 1));
+                    };
+                    let table = split
+                        .into_iter()
+                        .map(|(key, val)| match val {
+                            Json::Str(val) => Ok((key, val)),
+                            _ => Err(format!(
+                                "split {}: the value of '{}' must be a string",
+                                idx + 1,
+                                key
+                            )),
+                        })
+                        .collect::<Result<Table, _>>()?;
+                    tables.push(table);
+                }
+            }
+            ("name", _) => return Err("the value of 'name' must be a string".to_string()),
+            ("split", _) => return Err("the value of 'split' must be an array".to_string()),
+            (key, _) => return Err(format!("unknown key '{}'", key)),
+        }
+    }
+    Ok((name, tables))
+}
+
+struct JsonParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl JsonParser {
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.get(self.pos).copied();
+        self.pos += 1;
+        c
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            c => Err(format!(
+                "expected '{}' found {:?} at {}",
+                expected,
+                c,
+                self.pos - 1
+            )),
+        }
+    }
+
+    /// Returns true and skips `c` when it is the next character.
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        let found = self.chars.get(self.pos) == Some(&c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.chars.get(self.pos) {
+            Some('"') => self.string().map(Json::Str),
+            Some('[') => {
+                self.pos += 1;
+                let mut items = vec![];
+                if !self.eat(']') {
+                    loop {
+                        items.push(self.value()?);
+                        if self.eat(']') {
+                            break;
+                        }
+                        self.expect(',')?;
+                    }
+                }
+                Ok(Json::Arr(items))
+            }
+            Some('{') => {
+                self.pos += 1;
+                let mut fields = vec![];
+                if !self.eat('}') {
+                    loop {
+                        self.skip_whitespace();
+                        let key = self.string()?;
+                        self.expect(':')?;
+                        fields.push((key, self.value()?));
+                        if self.eat('}') {
+                            break;
+                        }
+                        self.expect(',')?;
+                    }
+                }
+                Ok(Json::Obj(fields))
+            }
+            c => Err(format!(
+                "expected a string, an array or an object found {:?} at {}",
+                c, self.pos
+            )),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let start = self.pos;
+        if self.next() != Some('"') {
+            return Err(format!("expected a string at {}", start));
+        }
+        let mut out = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(out),
+                Some('\\') => match self.next() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('r') => out.push('\r'),
+                    Some('b') => out.push('\u{8}'),
+                    Some('f') => out.push('\u{c}'),
+                    Some(c @ ('"' | '\\' | '/')) => out.push(c),
+                    Some('u') => {
+                        let hex = self
+                            .chars
+                            .get(self.pos..self.pos + 4)
+                            .map(|hex| hex.iter().collect::<String>())
+                            .ok_or_else(|| format!("unterminated string at {}", start))?;
+                        self.pos += 4;
+                        let c = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| format!("invalid escape '\\u{}'", hex))?;
+                        out.push(c);
+                    }
+                    c => return Err(format!("unknown escape '\\{:?}' at {}", c, self.pos - 1)),
+                },
+                Some(c) => out.push(c),
+                None => return Err(format!("unterminated string at {}", start)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML: &str = r#"
+# An example route
+name = "Any%"
+
+[[split]]
+id = "defeated_kirikiri_boss" # renamed, still read
+label = "Bozu"
+
+[[split]]
+id = "kodama_rush"
+condition = "inventory.number_of_kodamas >= 20"
+after = "defeat_kirikiri_boss"
+
+[[split]]
+condition = "quest.fox_wedding_end"
+after = "kodama_rush"
+"#;
+
+    const JSON: &str = r#"{
+        "name": "Any%",
+        "split": [
+            { "id": "defeated_kirikiri_boss", "label": "Bozu" },
+            {
+                "id": "kodama_rush",
+                "condition": "inventory.number_of_kodamas >= 20",
+                "after": "defeat_kirikiri_boss"
+            },
+            { "condition": "quest.fox_wedding_end", "after": "kodama_rush" }
+        ]
+    }"#;
+
+    fn summary(route: &Route) -> Vec<(&str, Option<&str>, Option<&str>, &str)> {
+        route
+            .splits
+            .iter()
+            .map(|s| {
+                (
+                    s.id.as_str(),
+                    s.condition.as_ref().map(|c| c.source()),
+                    s.after.as_deref(),
+                    s.label.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_toml() {
+        let route = Route::parse(TOML).unwrap();
+        assert_eq!(route.name, "Any%");
+        assert_eq!(
+            summary(&route),
+            [
+                ("defeat_kirikiri_boss", None, None, "Bozu"),
+                (
+                    "custom_split_kodama_rush",
+                    Some("inventory.number_of_kodamas >= 20"),
+                    Some("defeat_kirikiri_boss"),
+                    "custom_split_kodama_rush",
+                ),
+                (
+                    "custom_split_route_3",
+                    Some("quest.fox_wedding_end"),
+                    Some("custom_split_kodama_rush"),
+                    "custom_split_route_3",
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_json_like_toml() {
+        let toml = Route::parse(TOML).unwrap();
+        let json = Route::parse(JSON).unwrap();
+        assert_eq!(json.name, toml.name);
+        assert_eq!(summary(&json), summary(&toml));
+    }
+
+    #[test]
+    fn labels_default_to_the_split_name() {
+        let route = Route::parse("[[split]]\nid = \"defeat_kirikiri_boss\"").unwrap();
+        assert_eq!(
+            route.splits[0].label,
+            split_info("defeat_kirikiri_boss").unwrap().name
+        );
+    }
+
+    #[test]
+    fn dependency() {
+        let route = Route::parse(TOML).unwrap();
+        assert_eq!(route.dependency("defeat_kirikiri_boss"), None);
+        assert_eq!(
+            route.dependency("custom_split_kodama_rush"),
+            Some("defeat_kirikiri_boss")
+        );
+        assert_eq!(
+            route.dependency("custom_split_route_3"),
+            Some("custom_split_kodama_rush")
+        );
+        assert_eq!(route.dependency("not_in_the_route"), None);
+    }
+
+    #[test]
+    fn strips_comments_outside_strings() {
+        assert_eq!(strip_comment("a = \"b\" # c"), "a = \"b\" ");
+        assert_eq!(strip_comment("a = \"#b\""), "a = \"#b\"");
+        assert_eq!(strip_comment("a = '#b' # c"), "a = '#b' ");
+        assert_eq!(
+            strip_comment(r##"a = "say \"#1\"" # c"##),
+            r##"a = "say \"#1\"" "##
+        );
+        assert_eq!(strip_comment(r#"a = "\\" # c"#), r#"a = "\\" "#);
+        // Literal strings have no escapes
+        assert_eq!(strip_comment(r"a = 'C:\' # c"), r"a = 'C:\' ");
+    }
+
+    #[test]
+    fn reads_toml_strings() {
+        let route = Route::parse(
+            r##"
+name = 'C:\routes # all'
+[[split]]
+id = "defeat_kirikiri_boss"
+label = "The \"#1\" boss\t" # comment
+"##,
+        )
+        .unwrap();
+        assert_eq!(route.name, r"C:\routes # all");
+        assert_eq!(route.splits[0].label, "The \"#1\" boss\t");
+    }
+
+    #[test]
+    fn reads_json_strings() {
+        let route = Route::parse(
+            r#"{ "name": "A \"route\" \u00e9\/", "split": [ { "id": "defeat_kirikiri_boss" } ] }"#,
+        )
+        .unwrap();
+        assert_eq!(route.name, "A \"route\" é/");
+    }
+
+    #[test]
+    fn reports_toml_errors() {
+        let err = |src| Route::parse(src).unwrap_err();
+        assert_eq!(err("title = \"Any%\""), "line 1: unknown key 'title'");
+        assert_eq!(
+            err("[[split]]\nid = 1"),
+            "line 2: the value of 'id' must be a string"
+        );
+        assert_eq!(
+            err("[[split]]\nid"),
+            "line 2: expected `key = \"value\"` found 'id'"
+        );
+        assert_eq!(
+            err("[[split]]\nlabel = \"a\""),
+            "split 1: needs an `id` or a `condition`"
+        );
+        assert_eq!(
+            err("[[split]]\nlabel = \"\\q\""),
+            "line 2: unknown escape '\\Some('q')' in 'label'"
+        );
+        assert!(err("[[split]]\ncondition = \"nope.nope\"").starts_with("split 1: "));
+    }
+
+    #[test]
+    fn reports_json_errors() {
+        let err = |src| Route::parse(src).unwrap_err();
+        assert_eq!(err("{ \"title\": \"Any%\" }"), "unknown key 'title'");
+        assert_eq!(
+            err("{ \"split\": [ { \"id\": 1 } ] }"),
+            "expected a string, an array or an object found Some('1') at 21"
+        );
+        assert_eq!(
+            err("{ \"split\": [ { \"id\": [] } ] }"),
+            "split 1: the value of 'id' must be a string"
+        );
+        assert_eq!(
+            err("{ \"split\": [ \"id\" ] }"),
+            "split 1: must be an object"
+        );
+        assert_eq!(
+            err("{ \"split\": {} }"),
+            "the value of 'split' must be an array"
+        );
+        assert_eq!(err("{ \"name\": \"Any%\" } }"), "unexpected '}' at 19");
+        assert_eq!(
+            err("{ \"name\": \"Any%\" \"split\": [] }"),
+            "expected ',' found Some('\"') at 17"
+        );
+        assert_eq!(err("{ \"name\": \"Any% }"), "unterminated string at 10");
+    }
+}
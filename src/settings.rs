@@ -3,6 +3,8 @@ use asr::settings::{
     Gui,
 };
 
+use crate::completion::TOTAL_KODAMAS;
+
 #[derive(Gui, Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Category {
     /// Any%
@@ -12,6 +14,39 @@ pub enum Category {
     HundredPercent,
 }
 
+impl Category {
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::AnyPercent => "Any%",
+            Category::HundredPercent => "100%",
+        }
+    }
+}
+
+#[derive(Gui, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum GenerateSplitsFile {
+    /// Do nothing.
+    #[default]
+    Nothing,
+    /// From the enabled splits (or the route).
+    EnabledSplits,
+    /// From the Any% preset.
+    AnyPercent,
+    /// From the 100% preset.
+    HundredPercent,
+}
+
+#[derive(Gui, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ExportSettings {
+    /// Do nothing.
+    #[default]
+    Nothing,
+    /// Write an .autosplitter.xml file next to the splits file.
+    Fragment,
+    /// Replace the custom settings of the auto splitter in the splits file.
+    PatchSplitsFile,
+}
+
 #[derive(Gui, Debug, PartialEq, Eq, Clone, Copy)]
 pub enum NumberOfKodamas {
     /// Do not split for Kodama's found.
@@ -23,6 +58,46 @@ pub enum NumberOfKodamas {
     EveryFive,
     /// Split every 10 Kodama's found, this also splits on 35 (the total).
     EveryTen,
+    /// Split at the Kodama counts in the `kodama_thresholds` setting of the splits file.
+    Custom,
+}
+
+impl NumberOfKodamas {
+    /// The Kodama counts this setting splits at in a full run, `custom` is the list from the splits file.
+    pub fn thresholds(self, custom: &[i32]) -> Vec<i32> {
+        match self {
+            NumberOfKodamas::NoSplit => vec![],
+            NumberOfKodamas::EveryOne => (1..=TOTAL_KODAMAS).collect(),
+            NumberOfKodamas::EveryFive => (5..=TOTAL_KODAMAS).step_by(5).collect(),
+            NumberOfKodamas::EveryTen => vec![10, 20, 30, TOTAL_KODAMAS],
+            NumberOfKodamas::Custom => custom.to_vec(),
+        }
+    }
+}
+
+#[derive(Gui, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BossHealthSplits {
+    /// Only the splits listed in the splits file.
+    #[default]
+    Off,
+    /// Split at 50% health.
+    Half,
+    /// Split at 66% and 33% health.
+    Thirds,
+    /// Split at 75%, 50% and 25% health.
+    Quarters,
+}
+
+impl BossHealthSplits {
+    /// The health percentages to split at.
+    pub fn percents(self) -> &'static [f32] {
+        match self {
+            BossHealthSplits::Off => &[],
+            BossHealthSplits::Half => &[50.0],
+            BossHealthSplits::Thirds => &[66.0, 33.0],
+            BossHealthSplits::Quarters => &[75.0, 50.0, 25.0],
+        }
+    }
 }
 
 #[derive(Gui)]
@@ -36,15 +111,80 @@ pub struct Settings {
     #[filter((_, "*.lss"), (_, "*.lsl"))]
     pub lss_file: FileSelect,
 
+    /// Select a route file.
+    ///
+    /// A TOML or JSON file listing the splits of a route (Any%, 100%, All Bosses...), every split it lists is enabled.
+    #[filter((_, "*.toml"), (_, "*.json"))]
+    pub route_file: FileSelect,
+
+    /// Enable splits from the segment names of your splits file.
+    ///
+    /// Segment names like "Hashihime" or "Grapple" are matched to splits, which are enabled in segment order.
+    #[default = false]
+    pub auto_map_segments: bool,
+
+    /// The category of your run.
+    ///
+    /// Used as the category name of a generated splits file.
+    pub category: Category,
+
+    /// Boss practice mode.
+    ///
+    /// Times each boss fight on its own: the timer starts when a fight starts, splits when the boss is defeated and restarts when the fight is retried after a death. No other splits happen in this mode.
+    #[default = false]
+    pub boss_practice: bool,
+
+    /// Export these settings.
+    ///
+    /// Copies the current settings as an .lss AutoSplitterSettings block to set up another machine, this goes back to "Do nothing" once done.
+    pub export_settings: ExportSettings,
+
+    /// Generate a splits file.
+    ///
+    /// Writes an .lss with a segment per split and these settings next to the selected splits file, this goes back to "Do nothing" once done.
+    pub generate_splits_file: GenerateSplitsFile,
+
+    /// Ending
+    #[heading_level = 1]
+    _ending: Title,
+
+    /// Enable ending splits.
+    ///
+    /// Splits on the credits that end a run, turn this off when your run ends on a boss defeat instead.
+    #[default = true]
+    pub ending_splits: bool,
+
+    /// Split on credits appearing.
+    ///
+    /// This is once the game has been beaten.
+    #[default = false]
+    pub credits_roll: bool,
+
+    /// Split on credits appearing with 100% completion.
+    ///
+    /// The final split of a 100% run, use it instead of the credits split. It only splits when every Kodama, VS tablet, music sheet, omamori strap, daruma, ability, quest and boss is done, the "Missing" variable shows what isn't.
+    #[default = false]
+    pub credits_roll_complete: bool,
+
+    /// Quests
+    #[heading_level = 1]
+    _quests: Title,
+
+    /// Enable quest splits.
+    ///
+    /// Splits on starting and completing the quests given by Asahi, Tori, Shimeji, Rozu and the other characters.
+    #[default = true]
+    pub quest_splits: bool,
+
     /// Split on starting Asahi's staff quest.
     ///
     /// This is the quest where you collect bamboo to get past Asahi (your first encounter).
     #[default = false]
     pub asahi_staff_start: bool,
 
-    /// Split on completing Ashai's staff quest.
+    /// Split on completing Asahi's staff quest.
     ///
-    /// This is the quest where you collect bamboo to get past Ashai (your first encounter).
+    /// This is the quest where you collect bamboo to get past Asahi (your first encounter).
     #[default = false]
     pub asahi_staff_end: bool,
 
@@ -54,111 +194,117 @@ pub struct Settings {
     #[default = false]
     pub asahi_eye_of_beast_start: bool,
 
-    /// Split on completing Ashai's Eye of Beast quest.
+    /// Split on completing Asahi's Eye of Beast quest.
     ///
     /// This is the quest where you collect an eye for your kettle (your second encounter).
     #[default = false]
     pub asahi_eye_of_beast_end: bool,
 
-    /// Split on starting Shimeji Armadillo collection quest.
+    /// Split on talking to Asahi after the Armapillo boss.
     ///
-    /// This is the quest where you collect 4 Armadillos.
+    /// Asahi talks to you after the Particularly Unmanageable Armadillo is beaten.
     #[default = false]
-    pub shimeji_quest_start: bool,
+    pub asahi_post_armapillo_boss: bool,
 
-    /// Split on completing Shimeji Armadillo collection quest.
+    /// Split on Tori telling the bump prophecy.
     ///
-    /// This is the quest where you collect 4 Armadillos.
+    /// Tori (the bird) tells the prophecy of the bump.
     #[default = false]
-    pub shimeji_quest_end: bool,
+    pub tori_bump_told: bool,
 
-    /// Split on starting Rozu's Requiem quest.
+    /// Split on fulfilling Tori's bump prophecy.
     ///
-    /// This is the quest where the demo ended (bunny gives you a note).
+    /// You come back to Tori having fulfilled the bump prophecy.
     #[default = false]
-    pub rozus_requiem_start: bool,
+    pub tori_bump_end: bool,
 
-    /// Split on completing Rozu's Requiem quest.
+    /// Split on Tori telling the bat prophecy.
     ///
-    /// This is the quest where the demo ended (bunny gives you a note).
+    /// Tori (the bird) tells the prophecy of the bat, the staff swing given after KiriKiri Bozu.
     #[default = false]
-    pub rozus_requiem_end: bool,
+    pub tori_bat_told: bool,
 
-    /// Split on inserting the first feather in keyhole.
+    /// Split on fulfilling Tori's bat prophecy.
     ///
-    /// Upon inserting the first key into the west keyhole.
+    /// You come back to Tori having fulfilled the bat prophecy.
     #[default = false]
-    pub west_feather_in_keyhole: bool,
+    pub tori_bat_end: bool,
 
-    /// Split on inserting the second feather in keyhole.
+    /// Split on Tori telling the dash prophecy.
     ///
-    /// Upon inserting the second key into the east keyhole.
+    /// Tori (the bird) tells the prophecy of the dash, given with the Eye of the Beast.
     #[default = false]
-    pub east_feather_in_keyhole: bool,
+    pub tori_dash_told: bool,
 
-    /// Split on obtaining the first feather.
+    /// Split on fulfilling Tori's dash prophecy.
     ///
-    /// Upon obtaining the west side feather.
+    /// You come back to Tori having fulfilled the dash prophecy.
     #[default = false]
-    pub first_feather_key: bool,
+    pub tori_dash_end: bool,
 
-    /// Split on obtaining the second feather.
+    /// Split on starting Shimeji Armadillo collection quest.
     ///
-    /// Upon obtaining the east side feather.
+    /// This is the quest where you collect 4 Armadillos.
     #[default = false]
-    pub second_feather_key: bool,
+    pub shimeji_quest_start: bool,
 
-    /// Split on starting the Vermilion Stranger quest.
+    /// Split on completing Shimeji Armadillo collection quest.
     ///
-    /// This quest gives fast travel.
+    /// This is the quest where you collect 4 Armadillos.
     #[default = false]
-    pub vermilion_stranger_quest_start: bool,
+    pub shimeji_quest_end: bool,
 
-    /// Split on finding 1 tablet for VS quest.
+    /// Split on starting Rozu's Requiem quest.
     ///
-    /// No ordering to this, if you find a tablet this split will happen.
+    /// This is the quest where the demo ended (bunny gives you a note).
     #[default = false]
-    pub one_vs_tablet: bool,
+    pub rozus_requiem_start: bool,
 
-    /// Split on finding 2 tablets for VS quest.
+    /// Split on completing Rozu's Requiem quest.
     ///
-    /// No ordering to this, if you find 2 tablets this split will happen.
+    /// This is the quest where the demo ended (bunny gives you a note).
     #[default = false]
-    pub two_vs_tablet: bool,
+    pub rozus_requiem_end: bool,
 
-    /// Split on finding 3 tablets for VS quest.
+    /// Split on starting the Vermilion Stranger quest.
     ///
-    /// No ordering to this, if you find 3 tablets this split will happen.
+    /// This quest gives fast travel.
     #[default = false]
-    pub three_vs_tablet: bool,
+    pub vermilion_stranger_quest_start: bool,
 
-    /// Split on finding 4 tablets for VS quest.
+    /// Split on completing the Vermilion Stranger quest.
     ///
-    /// No ordering to this, if you find 4 tablets this split will happen.
+    /// This quest gives fast travel.
     #[default = false]
-    pub four_vs_tablet: bool,
+    pub vermilion_stranger_quest_end: bool,
 
-    /// Split on finding 5 tablets for VS quest, this is the final tablet.
+    /// Split on starting the fox wedding quest.
     ///
-    /// No ordering to this, if you find 5 tablets this split will happen.
+    /// This splits on starting of the fox wedding quest.
     #[default = false]
-    pub five_vs_tablet: bool,
+    pub fox_wedding_start: bool,
 
-    /// Split on completing the Vermilion Stranger quest.
+    /// Split on saving the groom from Jorogumo.
     ///
-    /// This quest gives fast travel.
+    /// This splits on saving the Fox from the burrows spider Jorogumo.
     #[default = false]
-    pub vermilion_stranger_quest_end: bool,
+    pub fox_wedding_save_groom: bool,
+
+    /// Split on finishing the fox wedding quest.
+    ///
+    /// This splits on completion of the fox wedding quest.
+    #[default = false]
+    pub fox_wedding_end: bool,
 
     /// Split on starting Kitsune Kifuda Quest.
     ///
-    /// This is the quest where you get the scroll from DaiTangu to "kill" Gashadoku.
+    /// The game's name for the fox wedding quest, this reads the same flag as the fox wedding start split.
     #[default = false]
     pub kitsune_kifuda_start: bool,
 
     /// Split on completing Kitsune Kifuda Quest.
     ///
-    /// This is the quest where you get the scroll from DaiTangu to "kill" Gashadoku.
+    /// The game's name for the fox wedding quest, this reads the same flag as the fox wedding end split and gives the scroll.
     #[default = false]
     pub kitsune_kifuda_end: bool,
 
@@ -174,60 +320,62 @@ pub struct Settings {
     #[default = false]
     pub infinite_kettle_end: bool,
 
-    /// Split on entering the first palace elevator.
+    /// Split on inserting the first feather in keyhole.
     ///
-    /// This is the first elevator when entering the castle.
+    /// Upon inserting the first key into the west keyhole.
     #[default = false]
-    pub elevator_e_up: bool,
+    pub west_feather_in_keyhole: bool,
 
-    /// Split on entering the first floor palace elevator.
+    /// Split on inserting the second feather in keyhole.
     ///
-    /// This is the second elevator up, so now on 1st floor going to 2nd.
+    /// Upon inserting the second key into the east keyhole.
     #[default = false]
-    pub elevator_1_up: bool,
+    pub east_feather_in_keyhole: bool,
 
-    /// Split on entering the second floor palace elevator.
-    ///
-    /// This is the second elevator up, so now on 2nd floor going to 3rd.
-    #[default = false]
-    pub elevator_2_up: bool,
+    /// Bosses
+    #[heading_level = 1]
+    _bosses: Title,
 
-    /// Split on entering the third floor palace elevator.
+    /// Enable boss splits.
     ///
-    /// This is the third elevator up, so now on 3rd floor going to 4th (last).
-    #[default = false]
-    pub elevator_3_up: bool,
+    /// Splits on boss fights starting, bosses being defeated and their health dropping. A start split happens on the first attempt only, so the segment before it times the way to the boss apart from the fight. Boss practice doesn't need this, only its health splits do.
+    #[default = true]
+    pub boss_splits: bool,
 
-    /// Split on starting the fox wedding quest.
+    /// Split on every boss's health.
     ///
-    /// This splits on starting of the fox wedding quest.
-    #[default = false]
-    pub fox_wedding_start: bool,
+    /// Splits when the health of any boss drops to these percentages, on top of the `boss_health_splits` of the splits file.
+    pub boss_health_percents: BossHealthSplits,
 
-    /// Split on saving the groom from Jorogumo.
+    /// Split on starting the Kiri Kiri Bozu fight.
     ///
-    /// This splits on saving the Fox from the burrows spider Jorogumo.
+    /// Fires when the fight against KiriKiri Bozu, the first boss, begins.
     #[default = false]
-    pub fox_wedding_save_groom: bool,
+    pub start_kirikiri_fight: bool,
 
-    /// Split on finishing the fox wedding quest.
+    /// Split on defeating Kiri Kiri Bozu.
     ///
-    /// This splits on completion of the fox wedding quest.
+    /// This is the first boss of the game KiriKiri Bozu.
     #[default = false]
-    pub fox_wedding_end: bool,
+    pub defeat_kirikiri_boss: bool,
 
-    /// Split on defeating Kiri Kiri Bozu.
+    /// Split on starting the Particularly Unmanageable Armadillo fight.
     ///
-    /// This is the first boss of the game KiriKiri Bozu.
-    /// TODO: currently there is no split for this.
+    /// Fires when the fight against the Particularly Unmanageable Armadillo, the second boss, begins.
     #[default = false]
-    pub defeated_kirikiri_boss: bool,
+    pub start_pua_fight: bool,
 
     /// Split on defeating Particularly Unmanageable Armadillo.
     ///
     /// This is the second boss of the game Particularly Unmanageable Armadillo.
     #[default = false]
-    pub defeated_pua_boss: bool,
+    pub defeat_pua_boss: bool,
+
+    /// Split on starting the Hashihime fight.
+    ///
+    /// Fires when the fight against Hashihime on the bridge begins.
+    #[default = false]
+    pub start_hashihime_fight: bool,
 
     /// Split on defeating Hashihime.
     ///
@@ -235,47 +383,95 @@ pub struct Settings {
     #[default = false]
     pub defeat_hashihime_boss: bool,
 
+    /// Split on starting the Kaboto Yokozuma fight.
+    ///
+    /// Fires when the fight against Kaboto Yokozuna, the beetle, begins.
+    #[default = false]
+    pub start_kaboto_fight: bool,
+
     /// Split on defeating Kaboto Yokozuma (the beetle).
     ///
     /// This is the fourth boss of the game Kaboto Yokozuma.
     #[default = false]
     pub defeat_kaboto_boss: bool,
 
+    /// Split on starting the Jorogumo fight.
+    ///
+    /// Fires when the fight against Jorogumo, the spider, begins.
+    #[default = false]
+    pub start_jorogumo_fight: bool,
+
     /// Split on defeating Jorogumo (the spider).
     ///
     /// This is the fifth boss of the game Jorogumo.
     #[default = false]
     pub defeat_jorogumo_boss: bool,
 
+    /// Split on starting the single KarasuTengu fight.
+    ///
+    /// Fires when the single Karasu Tengu fight (133 health), the first of the Tengu fights, begins.
+    #[default = false]
+    pub start_karasu_tengu_one_fight: bool,
+
     /// Split on defeating KarasuTengu the single bird Tengu.
     ///
     /// This is the first Tengu of the Trio.
     #[default = false]
     pub defeat_karasu_tengu_one_boss: bool,
 
+    /// Split on starting the KarasuTengu duo fight.
+    ///
+    /// Fires when the Karasu Tengu duo fight (225 health) begins.
+    #[default = false]
+    pub start_karasu_tengu_two_fight: bool,
+
     /// Split on defeating KarasuTengu the duo bird Tengu.
     ///
     /// This is the second Tengu of the Trio (two at once).
     #[default = false]
     pub defeat_karasu_tengu_two_boss: bool,
 
+    /// Split on starting the DaiTengu Trio fight.
+    ///
+    /// Fires when the Dai Tengu fight against all three Tengu begins.
+    #[default = false]
+    pub start_dai_tengu_fight: bool,
+
     /// Split on defeating DaiTengu Trio (all three birds).
     ///
     /// This is the final Tengu fight (all three of them).
     #[default = false]
     pub defeat_dai_tengu_boss: bool,
 
+    /// Split on starting the Gashadokuro fight.
+    ///
+    /// Fires when the fight against Gashadokuro, the giant skeleton, begins.
+    #[default = false]
+    pub start_gash_fight: bool,
+
     /// Split on defeating Gashadokuro (the giant skeleton).
     ///
     /// This is the sixth boss of the game Gashadokuro.
     #[default = false]
     pub defeat_gash_boss: bool,
 
-    /// Split on defeating Ashai.
+    /// Split on starting the Asahi fight.
+    ///
+    /// Fires when the fight against Asahi, the second to last boss, begins.
+    #[default = false]
+    pub start_asahi_fight: bool,
+
+    /// Split on defeating Asahi.
     ///
     /// This is the second to last boss of the game.
     #[default = false]
-    pub defeat_ashai_boss: bool,
+    pub defeat_asahi_boss: bool,
+
+    /// Split on starting the Sakura Shogun fight.
+    ///
+    /// Fires when the final fight against Sakura Shogun begins.
+    #[default = false]
+    pub start_sakura_fight: bool,
 
     /// Split on defeating Sakura Shogun.
     ///
@@ -283,6 +479,40 @@ pub struct Settings {
     #[default = false]
     pub defeat_sakura_boss: bool,
 
+    /// Split on starting the Yuki fight.
+    ///
+    /// Fires when a fight against Yuki begins. Where it happens in a run isn't known yet, the fight is logged whenever the game updates it.
+    #[default = false]
+    pub start_yuki_fight: bool,
+
+    /// Split on defeating Yuki.
+    ///
+    /// Fires when the game marks Yuki as defeated.
+    #[default = false]
+    pub defeat_yuki_boss: bool,
+
+    /// Split on starting the Amaterasu fight.
+    ///
+    /// Fires when a fight against Amaterasu begins, like Yuki's its place in a run isn't known yet and the fight is logged.
+    #[default = false]
+    pub start_amaterasu_fight: bool,
+
+    /// Split on defeating Amaterasu.
+    ///
+    /// Fires when the game marks Amaterasu as defeated.
+    #[default = false]
+    pub defeat_amaterasu_boss: bool,
+
+    /// Abilities
+    #[heading_level = 1]
+    _abilities: Title,
+
+    /// Enable ability splits.
+    ///
+    /// Splits on gaining the attack, bat, dash, hover, i-dash, grapple, hammer dash and wall jump abilities.
+    #[default = true]
+    pub ability_splits: bool,
+
     /// Split on gaining atack ability.
     ///
     /// This is given once bamboo is collected for Asahi.
@@ -331,6 +561,16 @@ pub struct Settings {
     #[default = false]
     pub can_wall_jump: bool,
 
+    /// Darumas
+    #[heading_level = 1]
+    _darumas: Title,
+
+    /// Enable daruma splits.
+    ///
+    /// Splits on getting a Daruma, upgrading it to two eyes and equipping it for the first time.
+    #[default = true]
+    pub daruma_splits: bool,
+
     /// Split on gaining Chomper Daruma.
     ///
     /// This is given early in Caves.
@@ -379,23 +619,318 @@ pub struct Settings {
     #[default = false]
     pub got_togichan_daruma: bool,
 
-    /// Split on credits appearing.
+    /// Split on Chomper reaching two eyes.
     ///
-    /// This is once the game has been beaten.
+    /// The second eye is painted once the daruma is fully upgraded.
     #[default = false]
-    pub credits_roll: bool,
+    pub upgraded_chomper_daruma: bool,
+
+    /// Split on Kaboomaru reaching two eyes.
+    ///
+    /// The second eye is painted once the daruma is fully upgraded.
+    #[default = false]
+    pub upgraded_kaboomaru_daruma: bool,
+
+    /// Split on Yuki reaching two eyes.
+    ///
+    /// The second eye is painted once the daruma is fully upgraded.
+    #[default = false]
+    pub upgraded_yuki_daruma: bool,
+
+    /// Split on Jingu reaching two eyes.
+    ///
+    /// The second eye is painted once the daruma is fully upgraded.
+    #[default = false]
+    pub upgraded_jingu_daruma: bool,
+
+    /// Split on Mamori reaching two eyes.
+    ///
+    /// The second eye is painted once the daruma is fully upgraded.
+    #[default = false]
+    pub upgraded_mamori_daruma: bool,
+
+    /// Split on Ken reaching two eyes.
+    ///
+    /// The second eye is painted once the daruma is fully upgraded.
+    #[default = false]
+    pub upgraded_ken_daruma: bool,
+
+    /// Split on PyroKun reaching two eyes.
+    ///
+    /// The second eye is painted once the daruma is fully upgraded.
+    #[default = false]
+    pub upgraded_pyro_daruma: bool,
+
+    /// Split on TogiChan reaching two eyes.
+    ///
+    /// The second eye is painted once the daruma is fully upgraded.
+    #[default = false]
+    pub upgraded_togichan_daruma: bool,
+
+    /// Split on equipping Chomper for the first time.
+    #[default = false]
+    pub equipped_chomper_daruma: bool,
+
+    /// Split on equipping Kaboomaru for the first time.
+    #[default = false]
+    pub equipped_kaboomaru_daruma: bool,
+
+    /// Split on equipping Yuki for the first time.
+    #[default = false]
+    pub equipped_yuki_daruma: bool,
+
+    /// Split on equipping Jingu for the first time.
+    #[default = false]
+    pub equipped_jingu_daruma: bool,
+
+    /// Split on equipping Mamori for the first time.
+    #[default = false]
+    pub equipped_mamori_daruma: bool,
+
+    /// Split on equipping Ken for the first time.
+    #[default = false]
+    pub equipped_ken_daruma: bool,
+
+    /// Split on equipping PyroKun for the first time.
+    #[default = false]
+    pub equipped_pyro_daruma: bool,
+
+    /// Split on equipping TogiChan for the first time.
+    #[default = false]
+    pub equipped_togichan_daruma: bool,
+
+    /// Collectibles
+    #[heading_level = 1]
+    _collectibles: Title,
+
+    /// Enable collectible splits.
+    ///
+    /// Splits on feather keys, VS tablets, Armapillos, music sheets, omamori straps, key items and Kodamas, the count splits of the splits file included.
+    #[default = true]
+    pub collectible_splits: bool,
+
+    /// Split on obtaining the first feather.
+    ///
+    /// Upon obtaining the west side feather.
+    #[default = false]
+    pub first_feather_key: bool,
+
+    /// Split on obtaining the second feather.
+    ///
+    /// Upon obtaining the east side feather.
+    #[default = false]
+    pub second_feather_key: bool,
+
+    /// Split on finding 1 tablet for VS quest.
+    ///
+    /// No ordering to this, if you find a tablet this split will happen.
+    #[default = false]
+    pub one_vs_tablet: bool,
+
+    /// Split on finding 2 tablets for VS quest.
+    ///
+    /// No ordering to this, if you find 2 tablets this split will happen.
+    #[default = false]
+    pub two_vs_tablet: bool,
+
+    /// Split on finding 3 tablets for VS quest.
+    ///
+    /// No ordering to this, if you find 3 tablets this split will happen.
+    #[default = false]
+    pub three_vs_tablet: bool,
+
+    /// Split on finding 4 tablets for VS quest.
+    ///
+    /// No ordering to this, if you find 4 tablets this split will happen.
+    #[default = false]
+    pub four_vs_tablet: bool,
+
+    /// Split on finding 5 tablets for VS quest, this is the final tablet.
+    ///
+    /// No ordering to this, if you find 5 tablets this split will happen.
+    #[default = false]
+    pub five_vs_tablet: bool,
+
+    /// Split on collecting 1 Armapillo for Shimeji's quest.
+    ///
+    /// No ordering to this, if you have collected 1 Armapillo this split will happen.
+    #[default = false]
+    pub one_armapillo: bool,
+
+    /// Split on collecting 2 Armapillos for Shimeji's quest.
+    ///
+    /// No ordering to this, if you have collected 2 Armapillos this split will happen.
+    #[default = false]
+    pub two_armapillo: bool,
+
+    /// Split on collecting 3 Armapillos for Shimeji's quest.
+    ///
+    /// No ordering to this, if you have collected 3 Armapillos this split will happen.
+    #[default = false]
+    pub three_armapillo: bool,
+
+    /// Split on collecting 4 Armapillos for Shimeji's quest, this is the last one.
+    ///
+    /// No ordering to this, if you have collected 4 Armapillos this split will happen.
+    #[default = false]
+    pub four_armapillo: bool,
+
+    /// Split on finding 1 music sheet.
+    ///
+    /// No ordering to this, if you have found 1 music sheet this split will happen.
+    #[default = false]
+    pub one_music_sheet: bool,
+
+    /// Split on finding 2 music sheets.
+    ///
+    /// No ordering to this, if you have found 2 music sheets this split will happen.
+    #[default = false]
+    pub two_music_sheet: bool,
+
+    /// Split on finding 3 music sheets, this is the last one before the grapple.
+    ///
+    /// No ordering to this, if you have found 3 music sheets this split will happen.
+    #[default = false]
+    pub three_music_sheet: bool,
+
+    /// Split on finding 1 omamori strap.
+    ///
+    /// No ordering to this, if you have found 1 omamori strap this split will happen.
+    #[default = false]
+    pub one_omamori_strap: bool,
+
+    /// Split on finding 2 omamori straps.
+    ///
+    /// No ordering to this, if you have found 2 omamori straps this split will happen.
+    #[default = false]
+    pub two_omamori_strap: bool,
+
+    /// Split on finding 3 omamori straps.
+    ///
+    /// No ordering to this, if you have found 3 omamori straps this split will happen.
+    #[default = false]
+    pub three_omamori_strap: bool,
+
+    /// Split on finding 4 omamori straps.
+    ///
+    /// No ordering to this, if you have found 4 omamori straps this split will happen.
+    #[default = false]
+    pub four_omamori_strap: bool,
+
+    /// Split on picking up the fragile egg.
+    ///
+    /// The fragile egg key item is in your inventory. There is no split for handing it in, the game only tracks whether you carry the egg and no quest flag for it is known.
+    #[default = false]
+    pub got_fragile_egg: bool,
+
+    /// Split on picking up the Kitsune Kifuda scroll.
+    ///
+    /// The scroll is given when the fox wedding quest is completed, so this happens with the fox wedding end split.
+    #[default = false]
+    pub got_kitsune_kifuda: bool,
 
     /// Split on some number of Kodama's found.
     ///
     /// These are the little turnup things that you pull out of the ground to build stuff.
     pub number_of_kodamas: NumberOfKodamas,
+
+    /// Elevators
+    #[heading_level = 1]
+    _elevators: Title,
+
+    /// Enable elevator splits.
+    ///
+    /// Splits on riding the palace elevators up and down and on the vertical chase.
+    #[default = true]
+    pub elevator_splits: bool,
+
+    /// Split on entering the first palace elevator.
+    ///
+    /// This is the first elevator when entering the castle.
+    #[default = false]
+    pub elevator_e_up: bool,
+
+    /// Split on entering the first floor palace elevator.
+    ///
+    /// This is the second elevator up, so now on 1st floor going to 2nd.
+    #[default = false]
+    pub elevator_1_up: bool,
+
+    /// Split on entering the second floor palace elevator.
+    ///
+    /// This is the second elevator up, so now on 2nd floor going to 3rd.
+    #[default = false]
+    pub elevator_2_up: bool,
+
+    /// Split on entering the third floor palace elevator.
+    ///
+    /// This is the third elevator up, so now on 3rd floor going to 4th (last).
+    #[default = false]
+    pub elevator_3_up: bool,
+
+    /// Split on taking the third floor palace elevator down.
+    ///
+    /// This is the first elevator down after the Asahi fight, so now on 4th floor going to 3rd.
+    #[default = false]
+    pub elevator_3_down: bool,
+
+    /// Split on taking the second floor palace elevator down.
+    ///
+    /// Now on 3rd floor going to 2nd.
+    #[default = false]
+    pub elevator_2_down: bool,
+
+    /// Split on taking the first floor palace elevator down.
+    ///
+    /// Now on 2nd floor going to 1st.
+    #[default = false]
+    pub elevator_1_down: bool,
+
+    /// Split on starting the vertical chase.
+    #[default = false]
+    pub vertical_chase_start: bool,
+
+    /// Split on completing the vertical chase.
+    ///
+    /// This splits on the first elevator or boss fight after the vertical chase started, dying during the chase doesn't split.
+    #[default = false]
+    pub vertical_chase_complete: bool,
+
+    /// Upgrades
+    #[heading_level = 1]
+    _upgrades: Title,
+
+    /// Enable upgrade splits.
+    ///
+    /// Splits on staff, base damage and Daruma boost upgrades, the first of each kind or every one.
+    #[default = true]
+    pub upgrade_splits: bool,
+
+    /// Split on the first staff upgrade.
+    ///
+    /// This is the first time the staff damage goes up.
+    #[default = false]
+    pub first_staff_upgrade: bool,
+
+    /// Split on the first base damage upgrade.
+    #[default = false]
+    pub first_base_damage_upgrade: bool,
+
+    /// Split on the first daruma boost upgrade.
+    #[default = false]
+    pub first_daruma_boost_upgrade: bool,
+
+    /// Split on every damage upgrade.
+    ///
+    /// Any staff, base or daruma boost damage going up counts, for a segment per upgrade.
+    #[default = false]
+    pub every_damage_upgrade: bool,
 }
 
-#[allow(dead_code)]
 // TODO: If no file has been selected here are some suggestions
 pub const ANY_PERCENT: &[&str] = &[
-    "defeated_kirikiri_boss",       // KiriKiri Bozu split
-    "defeated_pua_boss",            // PUA defeated (armadillo) split
+    "defeat_kirikiri_boss",         // KiriKiri Bozu split
+    "defeat_pua_boss",              // PUA defeated (armadillo) split
     "rozus_requiem_start",          // Rozu's Requiem quest start
     "defeat_hashihime_boss",        // Hashihime defeated (bridge) split
     "vermilion_stranger_quest_end", // Fast Travel split
@@ -409,17 +944,20 @@ pub const ANY_PERCENT: &[&str] = &[
     // "first_feather_key", // First feather (West ice palace)
     "second_feather_key", // DaiTangu second feather key (East ice palace)
     "defeat_gash_boss",   // Defeat Gashadokuro skeleton
-    "first_elevator_up",  // Palace then enter first elevator
-    "defeat_ashai_boss",  // Defeat Asahi boss
+    "elevator_e_up",      // Palace then enter first elevator
+    "defeat_asahi_boss",  // Defeat Asahi boss
     "defeat_sakura_boss", // Defeat Sakura Shogun final boss
     "credits_roll",       // Credits roll
 ];
 
-#[allow(dead_code)]
-// TODO: Make this actually have the 100% splits
 pub const HUNDRED_PERCENT: &[&str] = &[
-    "can_bat",                      // KiriKiri Bozu split
-    "defeated_pua_boss",            // PUA defeated (armadillo) split
+    "defeat_kirikiri_boss",         // KiriKiri Bozu split
+    "can_bat",                      // Bat from KiriKiri Bozu
+    "got_chomper_daruma",           // Chomper early in the caves
+    "defeat_pua_boss",              // PUA defeated (armadillo) split
+    "asahi_eye_of_beast_end",       // Eye of the Beast (dash)
+    "shimeji_quest_end",            // All four armapillos for Shimeji
+    "rozus_requiem_end",            // Rozu's Requiem quest end
     "defeat_hashihime_boss",        // Hashihime defeated (bridge) split
     "vermilion_stranger_quest_end", // Fast Travel split
     "defeat_kaboto_boss",           // Yokozuna Kaboto (hover) split
@@ -431,7 +969,257 @@ pub const HUNDRED_PERCENT: &[&str] = &[
     "can_wall_jump",                // Wall jump split (West ice palace)
     "second_feather_key",           // DaiTangu second feather key (East ice palace)
     "defeat_gash_boss",             // Defeat Gashadokuro skeleton
-    "first_elevator_up",            // Palace then enter first elevator
-    "defeat_ashai_boss",            // Defeat Asahi boss
+    "infinite_kettle_end",          // Infinite Kettle quest end
+    "five_vs_tablet",               // Last VS tablet
+    "three_music_sheet",            // Last music sheet
+    "four_omamori_strap",           // Last omamori strap
+    "elevator_e_up",                // Palace then enter first elevator
+    "defeat_asahi_boss",            // Defeat Asahi boss
     "defeat_sakura_boss",           // Defeat Sakura Shogun final boss
+    "credits_roll_complete",        // Credits roll with everything done
 ];
+
+/// The sections of the settings, each has a toggle that turns all of its splits off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitGroup {
+    Ending,
+    Quests,
+    Bosses,
+    Abilities,
+    Darumas,
+    Collectibles,
+    Elevators,
+    Upgrades,
+}
+
+impl SplitGroup {
+    /// The id of the section's toggle.
+    pub fn setting_id(self) -> &'static str {
+        match self {
+            SplitGroup::Ending => "ending_splits",
+            SplitGroup::Quests => "quest_splits",
+            SplitGroup::Bosses => "boss_splits",
+            SplitGroup::Abilities => "ability_splits",
+            SplitGroup::Darumas => "daruma_splits",
+            SplitGroup::Collectibles => "collectible_splits",
+            SplitGroup::Elevators => "elevator_splits",
+            SplitGroup::Upgrades => "upgrade_splits",
+        }
+    }
+}
+
+impl Settings {
+    pub fn group_enabled(&self, group: SplitGroup) -> bool {
+        match group {
+            SplitGroup::Ending => self.ending_splits,
+            SplitGroup::Quests => self.quest_splits,
+            SplitGroup::Bosses => self.boss_splits,
+            SplitGroup::Abilities => self.ability_splits,
+            SplitGroup::Darumas => self.daruma_splits,
+            SplitGroup::Collectibles => self.collectible_splits,
+            SplitGroup::Elevators => self.elevator_splits,
+            SplitGroup::Upgrades => self.upgrade_splits,
+        }
+    }
+
+    /// The Kodama counts to split at, none when the collectible splits are off.
+    pub fn kodama_thresholds(&self, custom: &[i32]) -> Vec<i32> {
+        if self.collectible_splits {
+            self.number_of_kodamas.thresholds(custom)
+        } else {
+            vec![]
+        }
+    }
+}
+
+/// A split that can be enabled, `id` is the `Settings` field (and `.lss` setting id).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitInfo {
+    pub id: &'static str,
+    /// A short name, used for segment names.
+    pub name: &'static str,
+    /// The settings section the split is in.
+    pub group: SplitGroup,
+    /// Phrases that identify this split in a segment name, every word of a phrase has to be in the segment name for it to match.
+    pub keywords: &'static [&'static str],
+}
+
+macro_rules! splits {
+    ($($group:ident { $($id:ident: $name:literal [$($kw:literal),* $(,)?]),* $(,)? })*) => {
+        /// Every boolean split in `Settings`, in settings order.
+        pub const SPLITS: &[SplitInfo] = &[
+            $($(SplitInfo {
+                id: stringify!($id),
+                name: $name,
+                group: SplitGroup::$group,
+                keywords: &[$($kw),*],
+            },)*)*
+        ];
+    };
+}
+
+splits! {
+    Ending {
+        credits_roll: "Credits" ["credits", "the end"],
+        credits_roll_complete: "Credits 100%" ["credits 100", "100 credits", "completion"],
+    }
+    Quests {
+        asahi_staff_start: "Asahi's Staff Quest Start" ["staff start", "bamboo start"],
+        asahi_staff_end: "Asahi's Staff Quest" ["staff", "bamboo"],
+        asahi_eye_of_beast_start: "Eye of the Beast Start" ["eye beast start", "eye start"],
+        asahi_eye_of_beast_end: "Eye of the Beast" ["eye beast", "eye"],
+        asahi_post_armapillo_boss: "Asahi After Armapillo" ["asahi after", "asahi post", "asahi armapillo"],
+        tori_bump_told: "Tori Bump Prophecy" ["bump prophecy", "tori bump"],
+        tori_bump_end: "Bump Prophecy Fulfilled" ["bump fulfilled", "bump prophecy end", "bump end"],
+        tori_bat_told: "Tori Bat Prophecy" ["bat prophecy", "tori bat"],
+        tori_bat_end: "Bat Prophecy Fulfilled" ["bat fulfilled", "bat prophecy end", "bat end"],
+        tori_dash_told: "Tori Dash Prophecy" ["dash prophecy", "tori dash"],
+        tori_dash_end: "Dash Prophecy Fulfilled" ["dash fulfilled", "dash prophecy end", "dash end"],
+        shimeji_quest_start: "Shimeji Quest Start" ["shimeji start", "armapillo start", "armadillo start"],
+        shimeji_quest_end: "Shimeji Quest" ["shimeji", "armapillos", "armadillos"],
+        rozus_requiem_start: "Rozu's Requiem Start" ["rozu", "requiem", "rozus requiem start"],
+        rozus_requiem_end: "Rozu's Requiem" ["rozu end", "requiem end", "rozus requiem end"],
+        vermilion_stranger_quest_start: "Vermilion Stranger Start" ["vermilion start", "stranger start"],
+        vermilion_stranger_quest_end: "Fast Travel" ["vermilion", "stranger", "fast travel"],
+        fox_wedding_start: "Fox Wedding Start" ["fox start", "wedding start"],
+        fox_wedding_save_groom: "Save the Groom" ["groom"],
+        fox_wedding_end: "Fox Wedding" ["fox", "wedding"],
+        kitsune_kifuda_start: "Kitsune Kifuda Start" ["kifuda start", "kitsune start"],
+        kitsune_kifuda_end: "Kitsune Kifuda" ["kifuda", "kitsune"],
+        infinite_kettle_start: "Infinite Kettle Start" ["kettle start"],
+        infinite_kettle_end: "Infinite Kettle" ["kettle"],
+        west_feather_in_keyhole: "West Keyhole" ["west keyhole", "first keyhole", "keyhole"],
+        east_feather_in_keyhole: "East Keyhole" ["east keyhole", "second keyhole", "keyhole"],
+    }
+    Bosses {
+        start_kirikiri_fight: "KiriKiri Bozu Fight" ["to kirikiri", "reach kirikiri", "to bozu", "reach bozu"],
+        defeat_kirikiri_boss: "KiriKiri Bozu" ["kirikiri", "bozu"],
+        start_pua_fight: "Particularly Unmanageable Armadillo Fight" ["to pua", "reach pua", "to unmanageable", "reach unmanageable"],
+        defeat_pua_boss: "Particularly Unmanageable Armadillo" ["pua", "unmanageable"],
+        start_hashihime_fight: "Hashihime Fight" ["to hashihime", "reach hashihime", "to bridge", "reach bridge"],
+        defeat_hashihime_boss: "Hashihime" ["hashihime", "bridge"],
+        start_kaboto_fight: "Yokozuna Kaboto Fight" ["to kaboto", "reach kaboto", "to yokozuna", "reach yokozuna"],
+        defeat_kaboto_boss: "Yokozuna Kaboto" ["kaboto", "yokozuna", "beetle"],
+        start_jorogumo_fight: "Jorogumo Fight" ["to jorogumo", "reach jorogumo", "to spider", "reach spider"],
+        defeat_jorogumo_boss: "Jorogumo" ["jorogumo", "spider"],
+        start_karasu_tengu_one_fight: "Karasu Tengu Fight" ["to karasu", "reach karasu"],
+        defeat_karasu_tengu_one_boss: "Karasu Tengu" ["karasu", "tengu 1", "first tengu"],
+        start_karasu_tengu_two_fight: "Karasu Tengu Duo Fight" ["to karasu duo", "reach karasu duo", "to tengu duo", "reach tengu duo"],
+        defeat_karasu_tengu_two_boss: "Karasu Tengu Duo" ["karasu duo", "tengu 2", "tengu duo", "second tengu"],
+        start_dai_tengu_fight: "Dai Tengu Fight" ["to dai tengu", "reach dai tengu", "to tengu trio", "reach tengu trio"],
+        defeat_dai_tengu_boss: "Dai Tengu" ["dai tengu", "daitengu", "tengu trio", "tengu 3", "tengu"],
+        start_gash_fight: "Gashadokuro Fight" ["to gashadokuro", "reach gashadokuro", "to gasha", "reach gasha"],
+        defeat_gash_boss: "Gashadokuro" ["gashadokuro", "gasha", "skeleton"],
+        start_asahi_fight: "Asahi Fight" ["to asahi", "reach asahi"],
+        defeat_asahi_boss: "Asahi" ["asahi"],
+        start_sakura_fight: "Sakura Shogun Fight" ["to shogun", "reach shogun", "to sakura", "reach sakura"],
+        defeat_sakura_boss: "Sakura Shogun" ["shogun", "sakura"],
+        start_yuki_fight: "Yuki Fight" ["to yuki", "reach yuki"],
+        defeat_yuki_boss: "Yuki" ["yuki boss", "defeat yuki"],
+        start_amaterasu_fight: "Amaterasu Fight" ["to amaterasu", "reach amaterasu"],
+        defeat_amaterasu_boss: "Amaterasu" ["amaterasu"],
+    }
+    Abilities {
+        can_attack: "Attack" ["attack"],
+        can_bat: "Bat" ["bat"],
+        can_dash: "Dash" ["dash"],
+        can_hover: "Hover" ["hover"],
+        can_idash: "Shade Cloak" ["idash", "i dash", "shade cloak", "cloak"],
+        can_grapple: "Grapple" ["grapple", "music sheets", "sheet music"],
+        can_hammer_dash: "Hammer Dash" ["hammer dash", "hammer", "mallet", "dive"],
+        can_wall_jump: "Wall Jump" ["wall jump", "walljump"],
+    }
+    Darumas {
+        got_chomper_daruma: "Chomper Daruma" ["chomper"],
+        got_kaboomaru_daruma: "Kaboomaru Daruma" ["kaboomaru"],
+        got_yuki_daruma: "Yuki Daruma" ["yuki"],
+        got_jingu_daruma: "Jingu Daruma" ["jingu"],
+        got_mamori_daruma: "Mamori Daruma" ["mamori"],
+        got_ken_daruma: "Ken Daruma" ["ken"],
+        got_pyro_daruma: "PyroKun Daruma" ["pyro", "pyrokun"],
+        got_togichan_daruma: "TogiChan Daruma" ["togichan", "toge"],
+        upgraded_chomper_daruma: "Chomper Two Eyes" ["chomper two eyes", "chomper upgraded"],
+        upgraded_kaboomaru_daruma: "Kaboomaru Two Eyes" ["kaboomaru two eyes", "kaboomaru upgraded"],
+        upgraded_yuki_daruma: "Yuki Two Eyes" ["yuki two eyes", "yuki upgraded"],
+        upgraded_jingu_daruma: "Jingu Two Eyes" ["jingu two eyes", "jingu upgraded"],
+        upgraded_mamori_daruma: "Mamori Two Eyes" ["mamori two eyes", "mamori upgraded"],
+        upgraded_ken_daruma: "Ken Two Eyes" ["ken two eyes", "ken upgraded"],
+        upgraded_pyro_daruma: "PyroKun Two Eyes" ["pyro two eyes", "pyro upgraded", "pyrokun two eyes", "pyrokun upgraded"],
+        upgraded_togichan_daruma: "TogiChan Two Eyes" ["togichan two eyes", "togichan upgraded", "toge two eyes", "toge upgraded"],
+        equipped_chomper_daruma: "Chomper Equipped" ["chomper equipped", "equip chomper"],
+        equipped_kaboomaru_daruma: "Kaboomaru Equipped" ["kaboomaru equipped", "equip kaboomaru"],
+        equipped_yuki_daruma: "Yuki Equipped" ["yuki equipped", "equip yuki"],
+        equipped_jingu_daruma: "Jingu Equipped" ["jingu equipped", "equip jingu"],
+        equipped_mamori_daruma: "Mamori Equipped" ["mamori equipped", "equip mamori"],
+        equipped_ken_daruma: "Ken Equipped" ["ken equipped", "equip ken"],
+        equipped_pyro_daruma: "PyroKun Equipped" ["pyro equipped", "equip pyro", "pyrokun equipped", "equip pyrokun"],
+        equipped_togichan_daruma: "TogiChan Equipped" ["togichan equipped", "equip togichan", "toge equipped", "equip toge"],
+    }
+    Collectibles {
+        first_feather_key: "First Feather" ["first feather", "west feather", "feather"],
+        second_feather_key: "Second Feather" ["second feather", "east feather", "feather"],
+        one_vs_tablet: "VS Tablet 1" ["tablet 1", "first tablet", "tablet"],
+        two_vs_tablet: "VS Tablet 2" ["tablet 2", "second tablet", "tablet"],
+        three_vs_tablet: "VS Tablet 3" ["tablet 3", "third tablet", "tablet"],
+        four_vs_tablet: "VS Tablet 4" ["tablet 4", "fourth tablet", "tablet"],
+        five_vs_tablet: "VS Tablet 5" ["tablet 5", "fifth tablet", "tablet"],
+        one_armapillo: "Armapillo 1" ["armapillo 1", "first armapillo", "armapillo"],
+        two_armapillo: "Armapillo 2" ["armapillo 2", "second armapillo", "armapillo"],
+        three_armapillo: "Armapillo 3" ["armapillo 3", "third armapillo", "armapillo"],
+        four_armapillo: "Armapillo 4" ["armapillo 4", "fourth armapillo", "armapillo"],
+        one_music_sheet: "Music Sheet 1" ["music sheet 1", "sheet 1", "first sheet", "sheet"],
+        two_music_sheet: "Music Sheet 2" ["music sheet 2", "sheet 2", "second sheet", "sheet"],
+        three_music_sheet: "Music Sheet 3" ["music sheet 3", "sheet 3", "third sheet", "sheet"],
+        one_omamori_strap: "Omamori Strap 1" ["strap 1", "first strap", "strap"],
+        two_omamori_strap: "Omamori Strap 2" ["strap 2", "second strap", "strap"],
+        three_omamori_strap: "Omamori Strap 3" ["strap 3", "third strap", "strap"],
+        four_omamori_strap: "Omamori Strap 4" ["strap 4", "fourth strap", "strap"],
+        got_fragile_egg: "Fragile Egg" ["egg"],
+        got_kitsune_kifuda: "Kitsune Kifuda Scroll" ["kifuda scroll", "scroll"],
+    }
+    Elevators {
+        elevator_e_up: "Palace Elevator" ["entrance elevator", "first elevator", "elevator", "palace"],
+        elevator_1_up: "Floor 1 Elevator" ["floor 1", "1st floor", "second elevator", "elevator"],
+        elevator_2_up: "Floor 2 Elevator" ["floor 2", "2nd floor", "third elevator", "elevator"],
+        elevator_3_up: "Floor 3 Elevator" ["floor 3", "3rd floor", "fourth elevator", "elevator"],
+        elevator_3_down: "Floor 3 Elevator Down" ["floor 3 down", "3rd floor down", "elevator down"],
+        elevator_2_down: "Floor 2 Elevator Down" ["floor 2 down", "2nd floor down", "elevator down"],
+        elevator_1_down: "Floor 1 Elevator Down" ["floor 1 down", "1st floor down", "elevator down"],
+        vertical_chase_start: "Vertical Chase" ["vertical chase start", "chase start", "chase"],
+        vertical_chase_complete: "Vertical Chase Complete" ["vertical chase end", "chase end", "escape"],
+    }
+    Upgrades {
+        first_staff_upgrade: "Staff Upgrade" ["staff upgrade", "staff damage"],
+        first_base_damage_upgrade: "Damage Upgrade" ["damage upgrade", "base damage"],
+        first_daruma_boost_upgrade: "Daruma Boost" ["daruma boost", "boost upgrade"],
+    }
+}
+
+/// Looks up a split by its settings id.
+pub fn split_info(id: &str) -> Option<&'static SplitInfo> {
+    SPLITS.iter().find(|s| s.id == id)
+}
+
+/// Setting ids that were renamed or have another common name, and the id they are now.
+///
+/// Ids are saved in runners' splits files so an old id keeps working after a rename, when both ids are saved the old id's value is kept.
+pub const SETTING_ALIASES: &[(&str, &str)] = &[
+    ("defeated_kirikiri_boss", "defeat_kirikiri_boss"),
+    ("defeated_pua_boss", "defeat_pua_boss"),
+    ("defeat_ashai_boss", "defeat_asahi_boss"),
+    ("first_elevator_up", "elevator_e_up"),
+    ("timing_splits", "ending_splits"),
+    ("defeated_yuki", "defeat_yuki_boss"),
+    ("defeated_amaterasu", "defeat_amaterasu_boss"),
+    // The names of the game's own fields
+    ("defeat_spider_boss", "defeat_jorogumo_boss"),
+    ("defeat_tengu_boss", "defeat_dai_tengu_boss"),
+];
+
+/// The current id of a setting.
+pub fn canonical_id(id: &str) -> &str {
+    SETTING_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == id)
+        .map_or(id, |(_, canonical)| canonical)
+}
@@ -0,0 +1,69 @@
+//! Damage upgrades, found as increases of the staff, base and daruma boost damage.
+
+/// The damage values the game keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageKind {
+    /// `EnemiesManager::staff_damage`
+    Staff,
+    /// `InventoryContainer::base_damage`
+    Base,
+    /// `DarumaManager::daruma_boost_damage`
+    DarumaBoost,
+}
+
+impl DamageKind {
+    pub const ALL: [DamageKind; 3] = [DamageKind::Staff, DamageKind::Base, DamageKind::DarumaBoost];
+
+    /// The name of this damage's timer variable.
+    pub fn variable(self) -> &'static str {
+        match self {
+            DamageKind::Staff => "Staff damage",
+            DamageKind::Base => "Base damage",
+            DamageKind::DarumaBoost => "Daruma boost damage",
+        }
+    }
+}
+
+/// The highest damage seen of each kind and how often it went up.
+///
+/// Only a value above the highest one seen is an upgrade, so damage that drops for a while (a boost running out) doesn't count twice when it comes back.
+#[derive(Debug, Default)]
+pub struct DamageUpgrades {
+    highest: [Option<f32>; 3],
+    upgrades: [u32; 3],
+}
+
+impl DamageUpgrades {
+    /// Records the current damage of `kind`, returns true when it is an upgrade.
+    ///
+    /// The first value seen is where the counting starts from.
+    pub fn update(&mut self, kind: DamageKind, damage: f32) -> bool {
+        let highest = &mut self.highest[kind as usize];
+        match *highest {
+            Some(high) if damage > high => {
+                *highest = Some(damage);
+                self.upgrades[kind as usize] += 1;
+                true
+            }
+            Some(_) => false,
+            None => {
+                *highest = Some(damage);
+                false
+            }
+        }
+    }
+
+    pub fn upgrades(&self, kind: DamageKind) -> u32 {
+        self.upgrades[kind as usize]
+    }
+
+    /// The upgrades of every kind.
+    pub fn total(&self) -> u32 {
+        self.upgrades.iter().sum()
+    }
+
+    /// Starts counting over, for a new run.
+    pub fn clear(&mut self) {
+        *self = DamageUpgrades::default();
+    }
+}